@@ -0,0 +1,24 @@
+#import bevy_ui::ui_vertex_output::UiVertexOutput
+
+// x holds the fraction of the cooldown still remaining, in [0, 1]
+@group(1) @binding(0) var<uniform> progress: vec4<f32>;
+@group(1) @binding(1) var<uniform> color: vec4<f32>;
+
+const TAU: f32 = 6.28318530718;
+
+@fragment
+fn fragment(in: UiVertexOutput) -> @location(0) vec4<f32> {
+    let offset = in.uv - vec2<f32>(0.5, 0.5);
+    if length(offset) > 0.5 {
+        return vec4<f32>(0.0);
+    }
+
+    // Angle measured clockwise from 12 o'clock, normalized to [0, 1)
+    let angle = fract(atan2(offset.x, -offset.y) / TAU + 1.0);
+
+    // Shade the part of the circle that hasn't recharged yet
+    if angle < progress.x {
+        return color;
+    }
+    return vec4<f32>(0.0);
+}
@@ -0,0 +1,46 @@
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+
+// Each effect is off at 0.0 strength
+struct ScreenEffects {
+    vignette_color: vec4<f32>,
+    flash_color: vec4<f32>,
+    vignette: f32,
+    scanlines: f32,
+    scanline_spacing: f32,
+    flash: f32,
+    aberration: f32,
+    time: f32,
+}
+
+@group(0) @binding(2) var<uniform> settings: ScreenEffects;
+
+const TAU: f32 = 6.28318530718;
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    var color = textureSample(screen_texture, texture_sampler, in.uv);
+
+    // Chromatic aberration pulls red and blue apart, more towards the edges
+    let offset = (in.uv - vec2<f32>(0.5, 0.5)) * 2.0 * settings.aberration / vec2<f32>(textureDimensions(screen_texture));
+    let red = textureSample(screen_texture, texture_sampler, in.uv + offset).r;
+    let blue = textureSample(screen_texture, texture_sampler, in.uv - offset).b;
+    color = vec4<f32>(red, color.g, blue, color.a);
+
+    // Low health vignette, bleeding in from the corners
+    let edge = smoothstep(0.25, 0.75, length(in.uv - vec2<f32>(0.5, 0.5)));
+    let vignette = clamp(edge * settings.vignette * 1.5, 0.0, 1.0);
+    color = vec4<f32>(mix(color.rgb, settings.vignette_color.rgb, vignette), color.a);
+
+    // CRT scanlines, slowly rolling down the screen
+    let line = (in.position.y - settings.time * 8.0) / settings.scanline_spacing;
+    let shade = 0.5 + 0.5 * cos(line * TAU);
+    color = vec4<f32>(color.rgb * (1.0 - settings.scanlines * shade), color.a);
+
+    // Hit flash fades the whole screen towards a single color
+    color = vec4<f32>(mix(color.rgb, settings.flash_color.rgb, settings.flash * 0.6), color.a);
+
+    return color;
+}
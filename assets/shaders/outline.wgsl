@@ -0,0 +1,38 @@
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+@group(0) @binding(0) var screen_texture: texture_2d<f32>;
+@group(0) @binding(1) var texture_sampler: sampler;
+
+struct OutlineSettings {
+    color: vec4<f32>,
+    thickness: f32,
+    threshold: f32,
+}
+
+@group(0) @binding(2) var<uniform> settings: OutlineSettings;
+
+fn luminance(uv: vec2<f32>) -> f32 {
+    return dot(textureSample(screen_texture, texture_sampler, uv).rgb, vec3<f32>(0.2126, 0.7152, 0.0722));
+}
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(screen_texture, texture_sampler, in.uv);
+    let step = settings.thickness / vec2<f32>(textureDimensions(screen_texture));
+
+    // Sobel filter over the brightness of the neighboring pixels
+    let top_left = luminance(in.uv + vec2<f32>(-step.x, -step.y));
+    let top = luminance(in.uv + vec2<f32>(0.0, -step.y));
+    let top_right = luminance(in.uv + vec2<f32>(step.x, -step.y));
+    let left = luminance(in.uv + vec2<f32>(-step.x, 0.0));
+    let right = luminance(in.uv + vec2<f32>(step.x, 0.0));
+    let bottom_left = luminance(in.uv + vec2<f32>(-step.x, step.y));
+    let bottom = luminance(in.uv + vec2<f32>(0.0, step.y));
+    let bottom_right = luminance(in.uv + vec2<f32>(step.x, step.y));
+    let gx = (top_right + 2.0 * right + bottom_right) - (top_left + 2.0 * left + bottom_left);
+    let gy = (bottom_left + 2.0 * bottom + bottom_right) - (top_left + 2.0 * top + top_right);
+
+    // Fade the line in just above the threshold so it doesn't flicker on soft edges
+    let edge = smoothstep(settings.threshold, settings.threshold * 1.5, length(vec2<f32>(gx, gy)));
+    return vec4<f32>(mix(color.rgb, settings.color.rgb, edge * settings.color.a), color.a);
+}
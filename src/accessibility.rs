@@ -0,0 +1,159 @@
+use bevy::ecs::query::QueryItem;
+use bevy::prelude::*;
+use bevy::render::extract_component::ExtractComponent;
+use bevy::render::render_resource::ShaderType;
+use serde::{Deserialize, Serialize};
+
+use crate::postprocess::{FullscreenEffect, FullscreenEffectPlugin};
+use crate::{Bullet, EnemyBullet};
+
+// Accessibility Plugin
+pub struct AccessibilityPlugin;
+
+impl Plugin for AccessibilityPlugin {
+    fn build(&self, app: &mut App) {
+        // Added before the other screen effects so scanlines and the spiral don't get outlined
+        app.add_plugins(FullscreenEffectPlugin::<OutlineSettings>::default())
+            .init_resource::<AccessibilitySettings>()
+            .add_systems(Update, (apply_game_speed, drive_outline))
+            .add_systems(PostUpdate, color_bullets);
+    }
+}
+
+/// Options for players who have trouble telling colors apart, are bothered by motion or need
+/// more time to react.
+#[derive(Resource, Clone, Debug)]
+pub struct AccessibilitySettings {
+    pub palette: Palette,
+    /// Leaves out screen shake, the melting circles and the spiral when the player dies.
+    pub reduced_motion: bool,
+    /// Outlines everything in the game world in a bright color.
+    pub high_contrast: bool,
+    /// How fast game time runs compared to real time.
+    pub game_speed: f32,
+}
+
+impl Default for AccessibilitySettings {
+    fn default() -> Self {
+        Self { palette: Palette::Standard, reduced_motion: false, high_contrast: false, game_speed: 1.0 }
+    }
+}
+
+/// Colors for what tells friend from foe: health bars and bullets.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum Palette {
+    /// Green and red health bars; bullets in their weapon's color.
+    #[default]
+    Standard,
+    /// Blue and orange, for deuteranopia and protanopia.
+    RedGreen,
+    /// Teal and red, for tritanopia.
+    BlueYellow,
+}
+
+/// The colors a palette picks. Bullets without a color keep the one they were spawned with.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PaletteColors {
+    pub player_health: Color,
+    pub enemy_health: Color,
+    pub player_bullet: Option<Color>,
+    pub enemy_bullet: Option<Color>,
+}
+
+impl Palette {
+    pub const ALL: [Palette; 3] = [Palette::Standard, Palette::RedGreen, Palette::BlueYellow];
+
+    /// Key of the palette's name in the string tables.
+    pub fn name(self) -> &'static str {
+        match self {
+            Palette::Standard => "palette.standard",
+            Palette::RedGreen => "palette.red_green",
+            Palette::BlueYellow => "palette.blue_yellow",
+        }
+    }
+
+    /// The colorblind palettes take their colors from the Okabe-Ito set, picking pairs that stay
+    /// apart for that kind of color blindness.
+    pub fn colors(self) -> PaletteColors {
+        match self {
+            Palette::Standard => PaletteColors {
+                player_health: Color::srgb(0.0, 0.8, 0.0),
+                enemy_health: Color::srgb(0.8, 0.0, 0.0),
+                player_bullet: None,
+                enemy_bullet: None,
+            },
+            Palette::RedGreen => PaletteColors {
+                player_health: Color::srgb(0.0, 0.45, 0.7),
+                enemy_health: Color::srgb(0.9, 0.6, 0.0),
+                player_bullet: Some(Color::srgb(0.35, 0.7, 0.9)),
+                enemy_bullet: Some(Color::srgb(0.95, 0.9, 0.25)),
+            },
+            Palette::BlueYellow => PaletteColors {
+                player_health: Color::srgb(0.0, 0.6, 0.45),
+                enemy_health: Color::srgb(0.85, 0.35, 0.0),
+                player_bullet: Some(Color::srgb(1.0, 1.0, 1.0)),
+                enemy_bullet: Some(Color::srgb(0.8, 0.45, 0.65)),
+            },
+        }
+    }
+}
+
+/// Draws a line along every sharp change in brightness. Matches the `OutlineSettings` uniform
+/// in `assets/shaders/outline.wgsl`.
+#[derive(Component, ShaderType, Clone, Copy, Default, PartialEq, Debug)]
+pub struct OutlineSettings {
+    pub color: Vec4,
+    /// Line width in screen pixels; 0 turns the outline off.
+    pub thickness: f32,
+    /// Brightness difference that starts to count as an edge, so floor details stay unlined.
+    pub threshold: f32,
+}
+
+impl OutlineSettings {
+    pub fn is_active(&self) -> bool {
+        self.thickness > 0.0
+    }
+}
+
+impl FullscreenEffect for OutlineSettings {
+    const SHADER: &'static str = "shaders/outline.wgsl";
+    const LABEL: &'static str = "outline";
+}
+
+impl ExtractComponent for OutlineSettings {
+    type QueryData = &'static OutlineSettings;
+    type QueryFilter = ();
+    type Out = OutlineSettings;
+
+    fn extract_component(settings: QueryItem<'_, '_, Self::QueryData>) -> Option<Self::Out> {
+        settings.is_active().then_some(*settings)
+    }
+}
+
+fn drive_outline(settings: Res<AccessibilitySettings>, mut query: Query<&mut OutlineSettings>) {
+    let outline = if settings.high_contrast {
+        OutlineSettings { color: Color::WHITE.to_linear().to_vec4(), thickness: 2.0, threshold: 0.3 }
+    } else {
+        OutlineSettings::default()
+    };
+    for mut camera in query.iter_mut() {
+        camera.set_if_neq(outline);
+    }
+}
+
+/// Speed is set apart from pausing, so hit stop and the pause menu keep working at any speed.
+fn apply_game_speed(settings: Res<AccessibilitySettings>, mut virtual_time: ResMut<Time<Virtual>>) {
+    if settings.is_changed() {
+        virtual_time.set_relative_speed(settings.game_speed);
+    }
+}
+
+/// Recolors new bullets to the palette before they are first drawn.
+fn color_bullets(settings: Res<AccessibilitySettings>, mut query: Query<(&mut Sprite, Has<EnemyBullet>), Added<Bullet>>) {
+    let colors = settings.palette.colors();
+    for (mut sprite, from_enemy) in query.iter_mut() {
+        if let Some(color) = if from_enemy { colors.enemy_bullet } else { colors.player_bullet } {
+            sprite.color = color;
+        }
+    }
+}
@@ -0,0 +1,304 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::tileset::{TileRef, Tileset};
+use crate::{DamageDealt, Dead, Enemy, GameState, LastDirection};
+
+// Animation Plugin
+pub struct AnimationPlugin;
+
+impl Plugin for AnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<AnimationEvent>().add_systems(
+            Update,
+            (choose_locomotion, play_hurt, play_deaths, advance_animations, flip_sprites, despawn_dead_enemies)
+                .chain()
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ClipKind {
+    Idle,
+    Walk,
+    Hurt,
+    Attack,
+    Die,
+}
+
+/// What happens when a clip reaches its last frame.
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum PlayMode {
+    /// Start over from the first frame.
+    Loop,
+    /// Stay on the last frame.
+    Once,
+    /// Go back to idling or walking.
+    Return,
+}
+
+impl ClipKind {
+    fn default_mode(self) -> PlayMode {
+        match self {
+            ClipKind::Idle | ClipKind::Walk => PlayMode::Loop,
+            ClipKind::Hurt | ClipKind::Attack => PlayMode::Return,
+            ClipKind::Die => PlayMode::Once,
+        }
+    }
+}
+
+/// A clip as written in the data files.
+#[derive(Deserialize, Clone, Debug)]
+pub struct ClipDef {
+    pub frames: Vec<TileRef>,
+    #[serde(default = "default_fps")]
+    pub fps: f32,
+    /// Defaults to looping for idle and walk, returning for hurt and attack, and once for die.
+    #[serde(default)]
+    pub mode: Option<PlayMode>,
+    /// Named events fired when the clip reaches a frame, e.g. `(frame: 2, name: "fire")`.
+    #[serde(default)]
+    pub events: Vec<FrameEvent>,
+}
+
+fn default_fps() -> f32 {
+    8.0
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct FrameEvent {
+    pub frame: usize,
+    pub name: String,
+}
+
+/// Every clip a character or enemy can play. Missing clips fall back to sensible defaults
+/// built from the entity's sprite.
+#[derive(Deserialize, Clone, Default, Debug)]
+#[serde(default)]
+pub struct Animations {
+    pub idle: Option<ClipDef>,
+    pub walk: Option<ClipDef>,
+    pub hurt: Option<ClipDef>,
+    pub attack: Option<ClipDef>,
+    pub die: Option<ClipDef>,
+}
+
+/// Sent when a playing clip reaches a frame with an event on it.
+#[derive(Message)]
+pub struct AnimationEvent {
+    pub entity: Entity,
+    pub name: String,
+}
+
+struct Clip {
+    frames: Vec<usize>,
+    fps: f32,
+    mode: PlayMode,
+    events: Vec<FrameEvent>,
+}
+
+impl Clip {
+    fn resolve(kind: ClipKind, def: &ClipDef, tileset: &Tileset) -> Self {
+        Self {
+            frames: def.frames.iter().map(|frame| tileset.index(frame)).collect(),
+            fps: def.fps.max(0.1),
+            mode: def.mode.unwrap_or(kind.default_mode()),
+            events: def.events.clone(),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Animator {
+    clips: HashMap<ClipKind, Clip>,
+    current: ClipKind,
+    frame: usize,
+    elapsed: f32,
+    finished: bool,
+    /// Whether the current frame's events have been sent yet.
+    emitted: bool,
+    last_position: Option<Vec2>,
+}
+
+impl Animator {
+    pub fn new(tileset: &Tileset, animations: &Animations, sprite: &TileRef) -> Self {
+        let base = tileset.index(sprite);
+        let mut clips = HashMap::new();
+
+        for (kind, def) in [
+            (ClipKind::Idle, &animations.idle),
+            (ClipKind::Walk, &animations.walk),
+            (ClipKind::Hurt, &animations.hurt),
+            (ClipKind::Attack, &animations.attack),
+            (ClipKind::Die, &animations.die),
+        ] {
+            if let Some(def) = def.as_ref().filter(|def| !def.frames.is_empty()) {
+                clips.insert(kind, Clip::resolve(kind, def, tileset));
+            }
+        }
+
+        clips.entry(ClipKind::Idle).or_insert_with(|| Clip {
+            frames: vec![base],
+            fps: 1.0,
+            mode: PlayMode::Loop,
+            events: Vec::new(),
+        });
+        if !clips.contains_key(&ClipKind::Walk) {
+            let idle = &clips[&ClipKind::Idle];
+            let walk = Clip { frames: idle.frames.clone(), fps: idle.fps, mode: PlayMode::Loop, events: Vec::new() };
+            clips.insert(ClipKind::Walk, walk);
+        }
+        // Without a death animation, the body briefly turns into a gravestone
+        clips.entry(ClipKind::Die).or_insert_with(|| Clip {
+            frames: vec![base, tileset.index(&"gravestone".into())],
+            fps: 4.0,
+            mode: PlayMode::Once,
+            events: Vec::new(),
+        });
+
+        Self {
+            clips,
+            current: ClipKind::Idle,
+            frame: 0,
+            elapsed: 0.0,
+            finished: false,
+            emitted: false,
+            last_position: None,
+        }
+    }
+
+    /// Restarts the given clip. Returns false if the entity has no such clip.
+    pub fn play(&mut self, kind: ClipKind) -> bool {
+        if !self.clips.contains_key(&kind) {
+            return false;
+        }
+        self.current = kind;
+        self.frame = 0;
+        self.elapsed = 0.0;
+        self.finished = false;
+        self.emitted = false;
+        true
+    }
+
+    pub fn current(&self) -> ClipKind {
+        self.current
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Idle and walk can be swapped freely; other clips play out first.
+    fn is_interruptible(&self) -> bool {
+        matches!(self.current, ClipKind::Idle | ClipKind::Walk)
+    }
+}
+
+/// Switches between idle and walk depending on whether the entity moved this frame.
+fn choose_locomotion(mut query: Query<(&Transform, &mut Animator), Without<Dead>>) {
+    for (transform, mut animator) in query.iter_mut() {
+        let position = transform.translation.truncate();
+        let moved = animator.last_position.is_some_and(|last| last.distance_squared(position) > 0.01);
+        animator.last_position = Some(position);
+
+        if !animator.is_interruptible() {
+            continue;
+        }
+        let kind = if moved { ClipKind::Walk } else { ClipKind::Idle };
+        if animator.current != kind {
+            animator.play(kind);
+        }
+    }
+}
+
+fn play_hurt(mut damage_dealt: MessageReader<DamageDealt>, mut query: Query<&mut Animator, Without<Dead>>) {
+    for hit in damage_dealt.read() {
+        if hit.killed || hit.status.is_some() {
+            continue;
+        }
+        if let Ok(mut animator) = query.get_mut(hit.target) {
+            if animator.is_interruptible() {
+                animator.play(ClipKind::Hurt);
+            }
+        }
+    }
+}
+
+fn play_deaths(mut query: Query<&mut Animator, Added<Dead>>) {
+    for mut animator in query.iter_mut() {
+        animator.play(ClipKind::Die);
+    }
+}
+
+/// Steps every animator forward, updating sprites and sending frame events.
+fn advance_animations(
+    time: Res<Time>,
+    mut events: MessageWriter<AnimationEvent>,
+    mut query: Query<(Entity, &mut Animator, &mut Sprite)>,
+) {
+    for (entity, mut animator, mut sprite) in query.iter_mut() {
+        let animator = &mut *animator;
+        let clip = &animator.clips[&animator.current];
+
+        if !animator.finished {
+            animator.elapsed += time.delta_secs();
+            let frame_time = 1.0 / clip.fps;
+            while animator.elapsed >= frame_time && !animator.finished {
+                animator.elapsed -= frame_time;
+                if animator.frame + 1 < clip.frames.len() {
+                    animator.frame += 1;
+                    animator.emitted = false;
+                } else {
+                    match clip.mode {
+                        PlayMode::Loop => {
+                            animator.frame = 0;
+                            animator.emitted = false;
+                        }
+                        PlayMode::Once | PlayMode::Return => animator.finished = true,
+                    }
+                }
+            }
+        }
+
+        if !animator.emitted {
+            animator.emitted = true;
+            for event in clip.events.iter().filter(|event| event.frame == animator.frame) {
+                events.write(AnimationEvent { entity, name: event.name.clone() });
+            }
+        }
+
+        let index = clip.frames[animator.frame.min(clip.frames.len() - 1)];
+        if let Some(atlas) = sprite.texture_atlas.as_mut() {
+            if atlas.index != index {
+                atlas.index = index;
+            }
+        }
+
+        if animator.finished && clip.mode == PlayMode::Return {
+            animator.play(ClipKind::Idle);
+        }
+    }
+}
+
+/// Sprites face right in the tileset, so mirror them while heading left.
+fn flip_sprites(mut query: Query<(&LastDirection, &mut Sprite), (Changed<LastDirection>, Without<Dead>)>) {
+    for (direction, mut sprite) in query.iter_mut() {
+        if direction.0.x.abs() > 0.01 {
+            let flip = direction.0.x < 0.0;
+            if sprite.flip_x != flip {
+                sprite.flip_x = flip;
+            }
+        }
+    }
+}
+
+fn despawn_dead_enemies(mut commands: Commands, query: Query<(Entity, &Animator), (With<Enemy>, With<Dead>)>) {
+    for (entity, animator) in query.iter() {
+        if animator.current() == ClipKind::Die && animator.is_finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
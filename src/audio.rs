@@ -0,0 +1,467 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use bevy::audio::Volume;
+use bevy::prelude::*;
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::data::RonAssetLoader;
+use crate::progression::LevelUp;
+use crate::{DamageDealt, GameState};
+
+// Sound Plugin
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<SoundTable>()
+            .register_asset_loader(RonAssetLoader::<SoundTable>::default())
+            .init_resource::<VolumeSettings>()
+            .add_message::<PlaySound>()
+            .add_systems(Startup, load_sound_table)
+            .add_systems(Update, trigger_sounds.run_if(in_state(GameState::Playing)))
+            .add_systems(Update, (build_sound_bank, play_sounds, update_music, apply_sfx_volume).chain().after(trigger_sounds));
+    }
+}
+
+/// Most sound effects playing at once, across all sounds.
+const MAX_VOICES: usize = 32;
+
+/// Volume buses, each from 0 to 1. Music and effects are both scaled by `master`.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct VolumeSettings {
+    pub master: f32,
+    pub music: f32,
+    pub sfx: f32,
+}
+
+impl Default for VolumeSettings {
+    fn default() -> Self {
+        Self { master: 0.8, music: 0.6, sfx: 0.8 }
+    }
+}
+
+/// Sound effects and music, loaded from `assets/data/sounds.ron`.
+#[derive(Asset, TypePath, Deserialize, Clone)]
+#[serde(default)]
+pub struct SoundTable {
+    pub sounds: HashMap<String, SoundDef>,
+    /// Track looped in each game state, keyed by the state's name.
+    pub music: HashMap<String, MusicDef>,
+    /// Seconds one track takes to fade into the next.
+    pub crossfade: f32,
+}
+
+impl Default for SoundTable {
+    /// Used if the sound table fails to load.
+    fn default() -> Self {
+        let sounds = [
+            ("shoot", SoundDef { source: SoundSource::Tone(880.0, 0.04), volume: 0.15, max_voices: 3, ..default() }),
+            ("hit", SoundDef { source: SoundSource::Tone(220.0, 0.05), volume: 0.2, ..default() }),
+            ("crit", SoundDef { source: SoundSource::Tone(330.0, 0.07), volume: 0.25, ..default() }),
+            ("enemy_death", SoundDef { source: SoundSource::Tone(110.0, 0.12), volume: 0.25, ..default() }),
+            ("player_hurt", SoundDef { source: SoundSource::Tone(90.0, 0.2), volume: 0.35, max_voices: 1, speed_variation: 0.0 }),
+            ("level_up", SoundDef { source: SoundSource::Tone(660.0, 0.3), volume: 0.3, max_voices: 1, speed_variation: 0.0 }),
+            ("game_over", SoundDef { source: SoundSource::Tone(70.0, 1.0), volume: 0.4, max_voices: 1, speed_variation: 0.0 }),
+        ];
+        let menu = MusicDef { source: SoundSource::Tone(110.0, 4.0), volume: 0.05 };
+        let music = [
+            ("Loading", menu.clone()),
+            ("Menu", menu.clone()),
+            ("CharacterSelect", menu.clone()),
+            ("Settings", menu.clone()),
+            ("Controls", menu.clone()),
+            ("HighScores", menu),
+            ("Playing", MusicDef { source: SoundSource::Tone(82.4, 4.0), volume: 0.06 }),
+            ("GameOver", MusicDef { source: SoundSource::Tone(55.0, 4.0), volume: 0.06 }),
+        ];
+        Self {
+            sounds: sounds.into_iter().map(|(name, def)| (name.to_string(), def)).collect(),
+            music: music.into_iter().map(|(state, def)| (state.to_string(), def)).collect(),
+            crossfade: 1.5,
+        }
+    }
+}
+
+/// Where a sound's samples come from.
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub enum SoundSource {
+    /// An audio file under `assets`.
+    File(String),
+    /// A plain beep of (frequency in Hz, seconds), for sounds that have no file yet.
+    Tone(f32, f32),
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct SoundDef {
+    pub source: SoundSource,
+    pub volume: f32,
+    /// Most copies of this sound playing at once. Requests past this are dropped.
+    pub max_voices: usize,
+    /// Each play is sped up or slowed down by up to this fraction, so repeats don't drone.
+    pub speed_variation: f32,
+}
+
+impl Default for SoundDef {
+    fn default() -> Self {
+        Self { source: SoundSource::Tone(440.0, 0.1), volume: 1.0, max_voices: 4, speed_variation: 0.1 }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct MusicDef {
+    pub source: SoundSource,
+    pub volume: f32,
+}
+
+impl Default for MusicDef {
+    fn default() -> Self {
+        Self { source: SoundSource::Tone(110.0, 4.0), volume: 1.0 }
+    }
+}
+
+/// Asks for a sound effect from the sound table to be played.
+#[derive(Message, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PlaySound(pub &'static str);
+
+#[derive(Resource)]
+struct SoundTableHandle(Handle<SoundTable>);
+
+fn load_sound_table(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(SoundTableHandle(asset_server.load("data/sounds.ron")));
+}
+
+enum SoundHandle {
+    File(Handle<AudioSource>),
+    Tone(Handle<Pitch>),
+}
+
+impl SoundHandle {
+    fn load(source: &SoundSource, asset_server: &AssetServer, pitches: &mut Assets<Pitch>) -> Self {
+        match source {
+            SoundSource::File(path) => SoundHandle::File(asset_server.load(path)),
+            SoundSource::Tone(frequency, seconds) => SoundHandle::Tone(pitches.add(Pitch::new(*frequency, Duration::from_secs_f32(*seconds)))),
+        }
+    }
+}
+
+/// The sound table with every sound loaded, so effects don't reload each time they play.
+#[derive(Resource)]
+struct SoundBank {
+    sounds: HashMap<String, (SoundDef, SoundHandle)>,
+    music: HashMap<String, (MusicDef, SoundHandle)>,
+    crossfade: f32,
+}
+
+impl SoundBank {
+    fn new(table: SoundTable, asset_server: &AssetServer, pitches: &mut Assets<Pitch>) -> Self {
+        let sounds = table
+            .sounds
+            .into_iter()
+            .map(|(name, def)| {
+                let handle = SoundHandle::load(&def.source, asset_server, pitches);
+                (name, (def, handle))
+            })
+            .collect();
+        let music = table
+            .music
+            .into_iter()
+            .map(|(state, def)| {
+                let handle = SoundHandle::load(&def.source, asset_server, pitches);
+                (state, (def, handle))
+            })
+            .collect();
+        Self { sounds, music, crossfade: table.crossfade }
+    }
+}
+
+/// A sound effect that is playing.
+#[derive(Component)]
+struct Voice {
+    sound: &'static str,
+    volume: f32,
+}
+
+/// A music track, fading in while it is current and out once another takes over.
+#[derive(Component)]
+struct MusicTrack {
+    source: SoundSource,
+    volume: f32,
+    /// From 0 to 1.
+    fade: f32,
+    fading_out: bool,
+}
+
+/// Turns gameplay messages into sound effects. Shots and the game over sound are requested
+/// where they happen.
+fn trigger_sounds(mut damage_dealt: MessageReader<DamageDealt>, mut level_ups: MessageReader<LevelUp>, mut sounds: MessageWriter<PlaySound>) {
+    for hit in damage_dealt.read() {
+        // Status ticks would buzz constantly, and the player's death has its own sound
+        if hit.status.is_some() || (hit.to_player && hit.killed) {
+            continue;
+        }
+        let sound = match (hit.to_player, hit.killed, hit.crit) {
+            (true, _, _) => "player_hurt",
+            (false, true, _) => "enemy_death",
+            (false, false, true) => "crit",
+            (false, false, false) => "hit",
+        };
+        sounds.write(PlaySound(sound));
+    }
+    if level_ups.read().count() > 0 {
+        sounds.write(PlaySound("level_up"));
+    }
+}
+
+/// (Re)builds the sound bank once the table loads or changes, falling back to the built-in
+/// table if it can't be loaded.
+fn build_sound_bank(
+    mut commands: Commands,
+    handle: Res<SoundTableHandle>,
+    tables: Res<Assets<SoundTable>>,
+    asset_server: Res<AssetServer>,
+    mut pitches: ResMut<Assets<Pitch>>,
+    mut events: MessageReader<AssetEvent<SoundTable>>,
+    bank: Option<Res<SoundBank>>,
+) {
+    let loaded = events.read().any(|event| event.is_loaded_with_dependencies(&handle.0) || event.is_modified(&handle.0));
+    let failed = bank.is_none() && asset_server.load_state(&handle.0).is_failed();
+    if !loaded && !failed {
+        return;
+    }
+    let table = tables.get(&handle.0).cloned().unwrap_or_default();
+    commands.insert_resource(SoundBank::new(table, &asset_server, &mut pitches));
+}
+
+/// Picks which of this frame's requests to play. Repeats of a sound within a frame play once,
+/// and nothing plays past its own voice limit or `MAX_VOICES` overall.
+fn select_voices(requests: impl IntoIterator<Item = &'static str>, playing: &HashMap<&'static str, usize>, max_voices: impl Fn(&str) -> Option<usize>) -> Vec<&'static str> {
+    let mut total: usize = playing.values().sum();
+    let mut selected = Vec::new();
+    for sound in requests {
+        if total >= MAX_VOICES || selected.contains(&sound) {
+            continue;
+        }
+        if max_voices(sound).is_some_and(|max| playing.get(sound).copied().unwrap_or(0) < max) {
+            selected.push(sound);
+            total += 1;
+        }
+    }
+    selected
+}
+
+fn play_sounds(mut commands: Commands, mut requests: MessageReader<PlaySound>, bank: Option<Res<SoundBank>>, volume: Res<VolumeSettings>, voice_query: Query<&Voice>) {
+    let Some(bank) = bank else {
+        requests.clear();
+        return;
+    };
+    let mut playing = HashMap::new();
+    for voice in voice_query.iter() {
+        *playing.entry(voice.sound).or_insert(0) += 1;
+    }
+    let selected = select_voices(requests.read().map(|request| request.0), &playing, |sound| bank.sounds.get(sound).map(|(def, _)| def.max_voices));
+
+    let mut rng = rand::rng();
+    for sound in selected {
+        let (def, handle) = &bank.sounds[sound];
+        let speed = 1.0 + def.speed_variation * rng.random_range(-1.0..=1.0);
+        let settings = PlaybackSettings::DESPAWN.with_volume(Volume::Linear(def.volume * volume.master * volume.sfx)).with_speed(speed);
+        let voice = Voice { sound, volume: def.volume };
+        match handle {
+            SoundHandle::File(source) => commands.spawn((AudioPlayer(source.clone()), settings, voice)),
+            SoundHandle::Tone(pitch) => commands.spawn((AudioPlayer(pitch.clone()), settings, voice)),
+        };
+    }
+}
+
+/// Crossfades to the current state's track whenever the state changes. Fades run on real
+/// time so pausing doesn't leave two tracks playing.
+fn update_music(
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+    bank: Option<Res<SoundBank>>,
+    volume: Res<VolumeSettings>,
+    time: Res<Time<Real>>,
+    mut track_query: Query<(Entity, &mut MusicTrack, Option<&mut AudioSink>)>,
+) {
+    let Some(bank) = bank else {
+        return;
+    };
+    if state.is_changed() || bank.is_changed() {
+        let wanted = bank.music.get(&format!("{:?}", state.get()));
+        let mut playing = false;
+        for (_, mut track, _) in track_query.iter_mut() {
+            // A track shared with the last state carries on rather than restarting
+            let keep = !playing && wanted.is_some_and(|(music, _)| music.source == track.source);
+            track.fading_out = !keep;
+            playing |= keep;
+        }
+        if let (Some((music, handle)), false) = (wanted, playing) {
+            let settings = PlaybackSettings::LOOP.with_volume(Volume::SILENT);
+            let track = MusicTrack { source: music.source.clone(), volume: music.volume, fade: 0.0, fading_out: false };
+            match handle {
+                SoundHandle::File(source) => commands.spawn((AudioPlayer(source.clone()), settings, track)),
+                SoundHandle::Tone(pitch) => commands.spawn((AudioPlayer(pitch.clone()), settings, track)),
+            };
+        }
+    }
+
+    let step = time.delta_secs() / bank.crossfade.max(0.01);
+    for (entity, mut track, sink) in track_query.iter_mut() {
+        track.fade = (track.fade + if track.fading_out { -step } else { step }).clamp(0.0, 1.0);
+        if track.fading_out && track.fade <= 0.0 {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        if let Some(mut sink) = sink {
+            sink.set_volume(Volume::Linear(track.volume * track.fade * volume.master * volume.music));
+        }
+    }
+}
+
+/// Applies a change to the effects bus to sounds already playing.
+fn apply_sfx_volume(volume: Res<VolumeSettings>, mut voice_query: Query<(&Voice, &mut AudioSink)>) {
+    if !volume.is_changed() {
+        return;
+    }
+    for (voice, mut sink) in voice_query.iter_mut() {
+        sink.set_volume(Volume::Linear(voice.volume * volume.master * volume.sfx));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::message::Messages;
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::physics::OnHit;
+    use crate::status::StatusKind;
+
+    fn hit(to_player: bool, killed: bool, crit: bool, status: Option<StatusKind>) -> DamageDealt {
+        DamageDealt {
+            target: Entity::PLACEHOLDER,
+            amount: 10.0,
+            crit,
+            position: Vec3::ZERO,
+            direction: Vec2::X,
+            to_player,
+            killed,
+            on_hit: OnHit::default(),
+            status,
+        }
+    }
+
+    /// Runs `trigger_sounds` over the given messages and returns the sounds it requested.
+    fn sounds_for(hits: Vec<DamageDealt>, level_ups: u32) -> Vec<PlaySound> {
+        let mut world = World::new();
+        world.init_resource::<Messages<DamageDealt>>();
+        world.init_resource::<Messages<LevelUp>>();
+        world.init_resource::<Messages<PlaySound>>();
+        world.write_message_batch(hits);
+        world.write_message_batch((0..level_ups).map(|level| LevelUp { level: level + 2 }));
+
+        world.run_system_once(trigger_sounds).unwrap();
+        world.resource::<Messages<PlaySound>>().iter_current_update_messages().copied().collect()
+    }
+
+    #[test]
+    fn hits_play_a_sound_for_each_outcome() {
+        let sounds = sounds_for(vec![hit(false, false, false, None), hit(false, false, true, None), hit(false, true, false, None), hit(true, false, false, None)], 0);
+        assert_eq!(sounds, [PlaySound("hit"), PlaySound("crit"), PlaySound("enemy_death"), PlaySound("player_hurt")]);
+    }
+
+    #[test]
+    fn status_ticks_and_the_killing_blow_are_silent() {
+        assert!(sounds_for(vec![hit(false, false, false, Some(StatusKind::Burn)), hit(true, true, false, None)], 0).is_empty());
+    }
+
+    #[test]
+    fn level_ups_play_once_per_frame() {
+        assert_eq!(sounds_for(Vec::new(), 3), [PlaySound("level_up")]);
+    }
+
+    #[test]
+    fn repeats_within_a_frame_play_once() {
+        let selected = select_voices(std::iter::repeat_n("hit", 200).chain(["crit"]), &HashMap::new(), |_| Some(4));
+        assert_eq!(selected, ["hit", "crit"]);
+    }
+
+    #[test]
+    fn sounds_stop_at_their_voice_limit() {
+        let playing = HashMap::from([("hit", 4), ("crit", 1)]);
+        assert_eq!(select_voices(["hit", "crit"], &playing, |_| Some(4)), ["crit"]);
+    }
+
+    #[test]
+    fn total_voices_are_capped() {
+        let playing = HashMap::from([("hit", MAX_VOICES - 1)]);
+        assert_eq!(select_voices(["crit", "enemy_death"], &playing, |_| Some(MAX_VOICES)), ["crit"]);
+    }
+
+    #[test]
+    fn unknown_sounds_are_ignored() {
+        assert!(select_voices(["missing"], &HashMap::new(), |_| None).is_empty());
+    }
+
+    /// Source, fade and whether it's fading out, for each music track in the world.
+    fn tracks(world: &mut World) -> Vec<(SoundSource, f32, bool)> {
+        let mut tracks: Vec<_> = world.query::<&MusicTrack>().iter(world).map(|track| (track.source.clone(), track.fade, track.fading_out)).collect();
+        tracks.sort_by(|a, b| a.1.total_cmp(&b.1));
+        tracks
+    }
+
+    #[test]
+    fn state_changes_crossfade_music() {
+        let menu = SoundSource::Tone(110.0, 4.0);
+        let battle = SoundSource::Tone(82.4, 4.0);
+        let mut world = World::new();
+        world.insert_resource(SoundBank {
+            sounds: HashMap::new(),
+            music: HashMap::from([
+                ("Menu".to_string(), (MusicDef { source: menu.clone(), volume: 1.0 }, SoundHandle::Tone(Handle::default()))),
+                ("Playing".to_string(), (MusicDef { source: battle.clone(), volume: 1.0 }, SoundHandle::Tone(Handle::default()))),
+            ]),
+            crossfade: 1.0,
+        });
+        world.insert_resource(State::new(GameState::Menu));
+        world.init_resource::<VolumeSettings>();
+        world.init_resource::<Time<Real>>();
+        let update_music = world.register_system(update_music);
+        let step = |world: &mut World, seconds: f32| {
+            world.resource_mut::<Time<Real>>().update_with_duration(Duration::from_secs_f32(seconds));
+            world.run_system(update_music).unwrap();
+        };
+
+        step(&mut world, 0.0);
+        step(&mut world, 1.0);
+        assert_eq!(tracks(&mut world), [(menu.clone(), 1.0, false)]);
+
+        world.insert_resource(State::new(GameState::Playing));
+        step(&mut world, 0.5);
+        assert_eq!(tracks(&mut world), [(battle.clone(), 0.0, false), (menu, 0.5, true)]);
+        step(&mut world, 0.5);
+        assert_eq!(tracks(&mut world), [(battle.clone(), 0.5, false)]);
+        step(&mut world, 0.5);
+        assert_eq!(tracks(&mut world), [(battle, 1.0, false)]);
+    }
+
+    #[test]
+    fn shipped_table_has_every_sound_the_game_plays() {
+        let table: SoundTable = ron::Options::default()
+            .with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME)
+            .from_str(include_str!("../assets/data/sounds.ron"))
+            .unwrap();
+        for sound in ["shoot", "hit", "crit", "enemy_death", "player_hurt", "level_up", "game_over"] {
+            assert!(table.sounds.contains_key(sound), "{sound} is missing");
+            assert!(SoundTable::default().sounds.contains_key(sound), "{sound} is missing from the fallback");
+        }
+        for state in ["Loading", "Menu", "CharacterSelect", "Settings", "Controls", "HighScores", "Playing", "GameOver"] {
+            assert!(table.music.contains_key(state), "{state} has no music");
+            assert!(SoundTable::default().music.contains_key(state), "{state} has no music in the fallback");
+        }
+    }
+}
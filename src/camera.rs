@@ -0,0 +1,204 @@
+use bevy::prelude::*;
+
+use crate::input::ActionState;
+use crate::physics::Explosion;
+use crate::{DamageDealt, Dead, Enemy, GameState, LastDirection, Player};
+
+// Camera Controller Plugin
+pub struct CameraControllerPlugin;
+
+impl Plugin for CameraControllerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraSettings>()
+            .init_resource::<WorldBounds>()
+            .add_systems(Update, (add_trauma, update_camera).chain().run_if(in_state(GameState::Playing)))
+            .add_systems(OnEnter(GameState::Menu), reset_camera);
+    }
+}
+
+/// Tuning for how the camera follows the player.
+#[derive(Resource)]
+pub struct CameraSettings {
+    /// How quickly the camera catches up, as an exponential rate per second.
+    pub follow_speed: f32,
+    /// Half size of the box around the camera the player can move in without it following.
+    pub dead_zone: Vec2,
+    /// How far ahead of the player the camera leads, in pixels.
+    pub look_ahead: f32,
+    /// Share of the look-ahead taken from the aim rather than the movement direction.
+    pub aim_weight: f32,
+    pub look_ahead_speed: f32,
+    /// Scales all screen shake; 0 turns it off.
+    pub shake_intensity: f32,
+    pub max_shake_offset: f32,
+    /// Largest shake rotation in radians.
+    pub max_shake_angle: f32,
+    pub shake_frequency: f32,
+    /// Trauma lost per second.
+    pub trauma_decay: f32,
+    /// Trauma added when the player takes a hit.
+    pub hit_trauma: f32,
+    /// Trauma added by an explosion right on top of the camera, fading out with distance.
+    pub explosion_trauma: f32,
+    pub explosion_trauma_range: f32,
+    /// Zoom while the screen is empty, and when it is crowded.
+    pub min_zoom: f32,
+    pub max_zoom: f32,
+    /// Enemies near the player counted towards the zoom.
+    pub density_radius: f32,
+    /// Nearby enemies needed to zoom all the way out.
+    pub crowd_size: usize,
+    pub zoom_speed: f32,
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        Self {
+            follow_speed: 5.0,
+            dead_zone: Vec2::new(40.0, 24.0),
+            look_ahead: 80.0,
+            aim_weight: 0.5,
+            look_ahead_speed: 2.5,
+            shake_intensity: 1.0,
+            max_shake_offset: 20.0,
+            max_shake_angle: 0.04,
+            shake_frequency: 18.0,
+            trauma_decay: 1.5,
+            hit_trauma: 0.35,
+            explosion_trauma: 0.8,
+            explosion_trauma_range: 800.0,
+            min_zoom: 1.0,
+            max_zoom: 1.5,
+            density_radius: 600.0,
+            crowd_size: 60,
+            zoom_speed: 0.8,
+        }
+    }
+}
+
+/// Edges of the playable map, if it has any. The camera never shows beyond them. The streamed
+/// world has no edges and leaves this at `None`; a bounded map sets it.
+#[derive(Resource, Default)]
+pub struct WorldBounds(pub Option<Rect>);
+
+/// State of the camera between frames. The shake is applied on top of `focus`, so it never
+/// pushes the camera off course.
+#[derive(Component)]
+pub struct CameraController {
+    focus: Vec2,
+    look_ahead: Vec2,
+    zoom: f32,
+    /// From 0 to 1. Shake grows with its square, so small knocks stay subtle.
+    trauma: f32,
+    last_target: Option<Vec2>,
+}
+
+impl Default for CameraController {
+    fn default() -> Self {
+        Self { focus: Vec2::ZERO, look_ahead: Vec2::ZERO, zoom: 1.0, trauma: 0.0, last_target: None }
+    }
+}
+
+impl CameraController {
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).clamp(0.0, 1.0);
+    }
+}
+
+fn add_trauma(
+    settings: Res<CameraSettings>,
+    mut damage_dealt: MessageReader<DamageDealt>,
+    mut explosions: MessageReader<Explosion>,
+    mut camera_query: Query<&mut CameraController>,
+) {
+    let Ok(mut controller) = camera_query.single_mut() else {
+        return;
+    };
+    for hit in damage_dealt.read() {
+        if hit.to_player && hit.status.is_none() {
+            controller.add_trauma(settings.hit_trauma);
+        }
+    }
+    for explosion in explosions.read() {
+        let falloff = 1.0 - explosion.position.distance(controller.focus) / settings.explosion_trauma_range.max(1.0);
+        controller.add_trauma(settings.explosion_trauma * falloff.max(0.0));
+    }
+}
+
+/// Smooth noise in [-1, 1], different for each `seed`.
+fn shake_noise(time: f32, seed: f32) -> f32 {
+    (time + seed).sin() * 0.5 + (time * 2.3 + seed * 1.7).sin() * 0.3 + (time * 4.1 + seed * 2.9).sin() * 0.2
+}
+
+fn update_camera(
+    time: Res<Time>,
+    real_time: Res<Time<Real>>,
+    settings: Res<CameraSettings>,
+    bounds: Res<WorldBounds>,
+    actions: Res<ActionState>,
+    player_query: Query<(&Transform, &LastDirection), With<Player>>,
+    enemy_query: Query<&Transform, (With<Enemy>, Without<Dead>)>,
+    mut camera_query: Query<(&mut Transform, &mut Projection, &mut CameraController, &Camera), (Without<Player>, Without<Enemy>)>,
+) {
+    let (Ok((player_transform, last_direction)), Ok((mut camera_transform, mut projection, mut controller, camera))) =
+        (player_query.single(), camera_query.single_mut())
+    else {
+        return;
+    };
+    let dt = time.delta_secs();
+    let target = player_transform.translation.truncate();
+    let controller = &mut *controller;
+
+    // Only move the camera once the player leaves the dead-zone, and then just enough to keep them in it
+    let offset = target - controller.focus;
+    let desired = controller.focus + offset - offset.clamp(-settings.dead_zone, settings.dead_zone);
+    controller.focus = controller.focus.lerp(desired, 1.0 - (-settings.follow_speed * dt).exp());
+
+    // Lead in the direction the player is walking and aiming
+    let moving = controller.last_target.is_some_and(|last| last.distance_squared(target) > 0.01);
+    controller.last_target = Some(target);
+    let walk = if moving { last_direction.0.truncate().normalize_or_zero() } else { Vec2::ZERO };
+    let aim = actions.aim(target).unwrap_or(Vec2::ZERO);
+    let lead = walk.lerp(aim, settings.aim_weight) * settings.look_ahead;
+    controller.look_ahead = controller.look_ahead.lerp(lead, 1.0 - (-settings.look_ahead_speed * dt).exp());
+
+    // Zoom out as the crowd around the player grows
+    let radius_squared = settings.density_radius * settings.density_radius;
+    let nearby = enemy_query.iter().filter(|enemy| enemy.translation.truncate().distance_squared(target) < radius_squared).count();
+    let crowd = (nearby as f32 / settings.crowd_size.max(1) as f32).min(1.0);
+    let zoom = settings.min_zoom + (settings.max_zoom - settings.min_zoom) * crowd;
+    controller.zoom += (zoom - controller.zoom) * (1.0 - (-settings.zoom_speed * dt).exp());
+    if let Projection::Orthographic(orthographic) = &mut *projection {
+        orthographic.scale = controller.zoom;
+    }
+
+    let mut position = controller.focus + controller.look_ahead;
+    if let (Some(bounds), Some(viewport)) = (bounds.0, camera.logical_viewport_size()) {
+        let half = viewport / 2.0 * controller.zoom;
+        let (min, max) = (bounds.min + half, bounds.max - half);
+        // A map smaller than the view is kept centered instead
+        position.x = if min.x <= max.x { position.x.clamp(min.x, max.x) } else { bounds.center().x };
+        position.y = if min.y <= max.y { position.y.clamp(min.y, max.y) } else { bounds.center().y };
+    }
+
+    // Shake runs on real time so it keeps going through hit-stop
+    controller.trauma = (controller.trauma - settings.trauma_decay * real_time.delta_secs()).max(0.0);
+    let shake = controller.trauma * controller.trauma * settings.shake_intensity;
+    let t = real_time.elapsed_secs() * settings.shake_frequency;
+    let shake_offset = Vec2::new(shake_noise(t, 0.0), shake_noise(t, 10.0)) * settings.max_shake_offset * shake;
+    let shake_angle = shake_noise(t, 20.0) * settings.max_shake_angle * shake;
+
+    camera_transform.translation = (position + shake_offset).extend(camera_transform.translation.z);
+    camera_transform.rotation = Quat::from_rotation_z(shake_angle);
+}
+
+fn reset_camera(mut camera_query: Query<(&mut Transform, &mut Projection, &mut CameraController)>) {
+    for (mut transform, mut projection, mut controller) in camera_query.iter_mut() {
+        *controller = CameraController::default();
+        transform.translation = transform.translation.with_xy(Vec2::ZERO);
+        transform.rotation = Quat::IDENTITY;
+        if let Projection::Orthographic(orthographic) = &mut *projection {
+            orthographic.scale = 1.0;
+        }
+    }
+}
@@ -0,0 +1,546 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::animation::Animations;
+use crate::data::RonAssetLoader;
+use crate::input::{Action, ActionState, Bindings};
+use crate::locale::{Localized, Strings};
+use crate::physics::OnHit;
+use crate::profile::{Achievement, Profile};
+use crate::status::{StatusApplication, StatusKind};
+use crate::tileset::{TileRef, Tileset};
+use crate::widgets::{self, ButtonPressed, FocusButton, Focused};
+use crate::{Dead, GameState, Health, Player};
+
+// Character Plugin
+pub struct CharacterPlugin;
+
+impl Plugin for CharacterPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<CharacterRoster>()
+            .register_asset_loader(RonAssetLoader::<CharacterRoster>::default())
+            .init_resource::<CharacterSelection>()
+            .add_systems(Startup, load_roster)
+            .add_systems(OnEnter(GameState::CharacterSelect), setup_character_select)
+            .add_systems(
+                Update,
+                (populate_character_select, character_select_input, update_character_cards)
+                    .chain()
+                    .run_if(in_state(GameState::CharacterSelect)),
+            )
+            .add_systems(OnExit(GameState::CharacterSelect), cleanup_character_select)
+            .add_systems(Update, apply_regeneration.run_if(in_state(GameState::Playing)));
+    }
+}
+
+/// All playable characters, loaded from `assets/data/characters.ron`.
+#[derive(Asset, TypePath, Deserialize)]
+pub struct CharacterRoster {
+    pub characters: Vec<Character>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct Character {
+    pub id: String,
+    pub name: String,
+    pub sprite: TileRef,
+    pub speed: f32,
+    pub health: f32,
+    pub weapon: WeaponStats,
+    pub perk: Perk,
+    #[serde(default)]
+    pub unlock: Unlock,
+    #[serde(default)]
+    pub animations: Animations,
+}
+
+impl Default for Character {
+    /// The original knight, used if the roster asset fails to load.
+    fn default() -> Self {
+        Self {
+            id: "gragusi".to_string(),
+            name: "Gragusi".to_string(),
+            sprite: "knight".into(),
+            speed: 200.0,
+            health: 100.0,
+            weapon: WeaponStats {
+                name: "Blaster".to_string(),
+                icon: "fire_bolt".into(),
+                damage: 25.0,
+                projectile_speed: 300.0,
+                cooldown: 0.25,
+                crit_chance: 0.1,
+                color: [1.0, 0.2, 0.0],
+                on_hit: OnHit {
+                    knockback: 150.0,
+                    stun: 0.0,
+                    status: vec![StatusApplication { kind: StatusKind::Burn, duration: 3.0, magnitude: 4.0, chance: 0.25 }],
+                },
+                pattern: FirePattern::default(),
+            },
+            perk: Perk::Regeneration(0.5),
+            unlock: Unlock::Free,
+            animations: Animations::default(),
+        }
+    }
+}
+
+impl Character {
+    pub fn is_unlocked(&self, profile: &Profile) -> bool {
+        match &self.unlock {
+            Unlock::Free => true,
+            Unlock::Coins(_) => profile.unlocked.contains(&self.id),
+            Unlock::Achievement(achievement) => achievement.is_met(profile),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+pub struct WeaponStats {
+    pub name: String,
+    /// Tile shown in the HUD weapon slot.
+    pub icon: TileRef,
+    pub damage: f32,
+    pub projectile_speed: f32,
+    /// Seconds between shots while fire is held, counted from the start of each burst.
+    pub cooldown: f32,
+    /// Chance for a shot to deal `CRIT_MULTIPLIER` times the damage.
+    #[serde(default)]
+    pub crit_chance: f32,
+    pub color: [f32; 3],
+    /// Knockback, stun and status effects applied by each projectile.
+    #[serde(default)]
+    pub on_hit: OnHit,
+    #[serde(default)]
+    pub pattern: FirePattern,
+}
+
+/// How many projectiles a shot fires and how they are spread out.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct FirePattern {
+    /// Projectiles fired side by side with each shot.
+    pub projectiles: u32,
+    /// Angle in degrees between the outermost projectiles of a shot.
+    pub spread: f32,
+    /// Shots fired in quick succession each time the weapon goes off.
+    pub burst: u32,
+    /// Seconds between the shots of a burst.
+    pub burst_interval: f32,
+}
+
+impl Default for FirePattern {
+    fn default() -> Self {
+        Self { projectiles: 1, spread: 0.0, burst: 1, burst_interval: 0.08 }
+    }
+}
+
+impl FirePattern {
+    /// Directions of the projectiles in one shot, fanned out evenly around `aim`.
+    pub fn directions(&self, aim: Vec2) -> impl Iterator<Item = Vec2> + '_ {
+        let count = self.projectiles.max(1);
+        (0..count).map(move |index| {
+            let offset = if count == 1 { 0.0 } else { index as f32 / (count - 1) as f32 - 0.5 };
+            Vec2::from_angle((self.spread * offset).to_radians()).rotate(aim)
+        })
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Default)]
+pub enum Unlock {
+    #[default]
+    Free,
+    /// Bought from the select screen with coins earned across runs.
+    Coins(u32),
+    Achievement(Achievement),
+}
+
+/// Passive bonus a character starts the run with.
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub enum Perk {
+    /// Heals this much health per second.
+    Regeneration(f32),
+    /// Fraction of incoming damage ignored.
+    Armor(f32),
+    /// Heals this much health per kill.
+    Vampirism(f32),
+}
+
+impl Perk {
+    pub fn description(&self, strings: &Strings) -> String {
+        match *self {
+            Perk::Regeneration(rate) => strings.format("perk.regeneration", &[("rate", &rate)]),
+            Perk::Armor(fraction) => strings.format("perk.armor", &[("percent", &format!("{:.0}", fraction * 100.0))]),
+            Perk::Vampirism(heal) => strings.format("perk.vampirism", &[("heal", &heal)]),
+        }
+    }
+
+    pub fn icon(&self) -> TileRef {
+        match self {
+            Perk::Regeneration(_) => "heart".into(),
+            Perk::Armor(_) => "shield".into(),
+            Perk::Vampirism(_) => "potion".into(),
+        }
+    }
+}
+
+/// The player's perk together with the level it has been upgraded to.
+#[derive(Component)]
+pub struct Passive {
+    pub perk: Perk,
+    pub level: u32,
+}
+
+impl Passive {
+    pub fn new(perk: Perk) -> Self {
+        Self { perk, level: 1 }
+    }
+
+    /// Damage actually taken by a player with this passive. Armor stacks multiplicatively per level.
+    pub fn reduce_damage(passive: Option<&Passive>, damage: f32) -> f32 {
+        match passive {
+            Some(Passive { perk: Perk::Armor(fraction), level }) => {
+                damage * (1.0 - fraction.clamp(0.0, 1.0)).powi(*level as i32)
+            }
+            _ => damage,
+        }
+    }
+
+    /// Health restored to a player with this passive when they kill an enemy.
+    pub fn heal_on_kill(passive: Option<&Passive>) -> f32 {
+        match passive {
+            Some(Passive { perk: Perk::Vampirism(heal), level }) => heal * *level as f32,
+            _ => 0.0,
+        }
+    }
+}
+
+pub const CRIT_MULTIPLIER: f32 = 2.0;
+
+/// The player's gun, built from the character's starting weapon.
+#[derive(Component)]
+pub struct Weapon {
+    pub icon: TileRef,
+    pub level: u32,
+    pub base_damage: f32,
+    pub projectile_speed: f32,
+    pub crit_chance: f32,
+    pub color: Color,
+    pub on_hit: OnHit,
+    pub pattern: FirePattern,
+    pub cooldown: Timer,
+    /// Shots left in the current burst, fired each time `burst_timer` runs out.
+    pub burst_remaining: u32,
+    pub burst_timer: Timer,
+}
+
+impl Weapon {
+    /// Each level past the first adds a quarter of the base damage.
+    pub fn damage(&self) -> f32 {
+        self.base_damage * (1.0 + 0.25 * (self.level - 1) as f32)
+    }
+}
+
+impl From<&WeaponStats> for Weapon {
+    fn from(stats: &WeaponStats) -> Self {
+        let mut cooldown = Timer::from_seconds(stats.cooldown, TimerMode::Once);
+        cooldown.finish();
+        Self {
+            icon: stats.icon.clone(),
+            level: 1,
+            base_damage: stats.damage,
+            projectile_speed: stats.projectile_speed,
+            crit_chance: stats.crit_chance,
+            color: Color::srgb(stats.color[0], stats.color[1], stats.color[2]),
+            on_hit: stats.on_hit.clone(),
+            pattern: stats.pattern.clone(),
+            cooldown,
+            burst_remaining: 0,
+            burst_timer: Timer::from_seconds(stats.pattern.burst_interval, TimerMode::Once),
+        }
+    }
+}
+
+/// Handle to the roster. A default handle never loads, leaving the built-in character.
+#[derive(Resource, Default)]
+pub struct CharacterRosterHandle(Handle<CharacterRoster>);
+
+/// Index into the roster of the character picked on the select screen.
+#[derive(Resource, Default)]
+pub struct CharacterSelection(pub usize);
+
+/// Resolves the picked character, falling back to the default one if the roster isn't loaded.
+#[derive(SystemParam)]
+pub struct SelectedCharacter<'w> {
+    handle: Res<'w, CharacterRosterHandle>,
+    rosters: Res<'w, Assets<CharacterRoster>>,
+    selection: Res<'w, CharacterSelection>,
+}
+
+impl SelectedCharacter<'_> {
+    pub fn get(&self) -> Character {
+        self.rosters
+            .get(&self.handle.0)
+            .and_then(|roster| roster.characters.get(self.selection.0))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// The starting weapon of every character in the roster.
+    #[cfg(feature = "dev")]
+    pub fn weapons(&self) -> Vec<WeaponStats> {
+        match self.rosters.get(&self.handle.0) {
+            Some(roster) => roster.characters.iter().map(|character| character.weapon.clone()).collect(),
+            None => vec![Character::default().weapon],
+        }
+    }
+}
+
+#[derive(Component)]
+struct CharacterSelectUI;
+
+#[derive(Component)]
+struct CharacterCardRow;
+
+/// A card to pick or unlock a character with, by its index in the roster.
+#[derive(Component)]
+struct CharacterCard(usize);
+
+#[derive(Component)]
+struct CharacterSelectBack;
+
+#[derive(Component)]
+struct CardStatusText(usize);
+
+#[derive(Component)]
+struct CoinsText;
+
+fn load_roster(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(CharacterRosterHandle(asset_server.load("data/characters.ron")));
+}
+
+fn setup_character_select(mut commands: Commands, asset_server: Res<AssetServer>, bindings: Res<Bindings>) {
+    let font = asset_server.load(widgets::FONT);
+    commands.spawn((widgets::screen(), CharacterSelectUI)).with_children(|parent| {
+        parent.spawn(widgets::title(&font, "characters.title"));
+        parent.spawn((
+            Node {
+                flex_direction: FlexDirection::Row,
+                flex_wrap: FlexWrap::Wrap,
+                justify_content: JustifyContent::Center,
+                column_gap: Val::Px(16.0),
+                row_gap: Val::Px(16.0),
+                ..default()
+            },
+            CharacterCardRow,
+        ));
+        parent.spawn((
+            Text::new(""),
+            TextFont {
+                font: font.clone(),
+                font_size: 20.0,
+                ..default()
+            },
+            TextColor(Color::srgb(1.0, 0.85, 0.2)),
+            CoinsText,
+        ));
+        parent.spawn((widgets::button(&font, "common.back"), CharacterSelectBack));
+        parent.spawn(widgets::hint(
+            &font,
+            Localized::new("characters.hint")
+                .with("left", bindings.label(Action::MoveLeft))
+                .with("right", bindings.label(Action::MoveRight))
+                .with("confirm", bindings.label(Action::Confirm))
+                .with("back", bindings.label(Action::Back)),
+        ));
+    });
+}
+
+/// Fills in the character cards once the roster asset is available.
+fn populate_character_select(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    tileset: Res<Tileset>,
+    strings: Strings,
+    roster_handle: Res<CharacterRosterHandle>,
+    rosters: Res<Assets<CharacterRoster>>,
+    selection: Res<CharacterSelection>,
+    row_query: Query<Entity, With<CharacterCardRow>>,
+    card_query: Query<(), With<CharacterCard>>,
+    focused_query: Query<Entity, With<Focused>>,
+) {
+    if !card_query.is_empty() {
+        return;
+    }
+    let (Some(roster), Ok(row)) = (rosters.get(&roster_handle.0), row_query.single()) else {
+        return;
+    };
+
+    // Start on the card picked last time rather than the back button
+    for entity in focused_query.iter() {
+        commands.entity(entity).remove::<Focused>();
+    }
+    let font = asset_server.load(widgets::FONT);
+    commands.entity(row).with_children(|parent| {
+        for (index, character) in roster.characters.iter().enumerate() {
+            let mut card = parent.spawn((
+                FocusButton,
+                Node {
+                    width: Val::Px(260.0),
+                    padding: UiRect::all(Val::Px(12.0)),
+                    border: UiRect::all(Val::Px(3.0)),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(8.0),
+                    ..default()
+                },
+                CharacterCard(index),
+            ));
+            if index == selection.0.min(roster.characters.len() - 1) {
+                card.insert(Focused);
+            }
+            card.with_children(|card| {
+                card.spawn((
+                    tileset.image_node(tileset.index(&character.sprite)),
+                    Node {
+                        width: Val::Px(64.0),
+                        height: Val::Px(64.0),
+                        ..default()
+                    },
+                ));
+                card.spawn((
+                    Text::new(character.name.clone()),
+                    TextFont {
+                        font: font.clone(),
+                        font_size: 22.0,
+                        ..default()
+                    },
+                    TextColor(Color::WHITE),
+                ));
+                card.spawn((
+                    Localized::new("characters.stats")
+                        .with("health", character.health)
+                        .with("speed", character.speed)
+                        .with("weapon", &character.weapon.name)
+                        .with("damage", character.weapon.damage)
+                        .with("perk", character.perk.description(&strings)),
+                    TextFont {
+                        font: font.clone(),
+                        font_size: 14.0,
+                        ..default()
+                    },
+                    TextColor(Color::srgb(0.8, 0.8, 0.8)),
+                ));
+                card.spawn((
+                    Text::new(""),
+                    TextFont {
+                        font: font.clone(),
+                        font_size: 14.0,
+                        ..default()
+                    },
+                    TextColor(Color::srgb(1.0, 0.85, 0.2)),
+                    CardStatusText(index),
+                ));
+            });
+        }
+    });
+}
+
+fn character_select_input(
+    actions: Res<ActionState>,
+    mut pressed: MessageReader<ButtonPressed>,
+    focused_query: Query<&CharacterCard, Added<Focused>>,
+    card_query: Query<&CharacterCard>,
+    back_query: Query<(), With<CharacterSelectBack>>,
+    roster_handle: Res<CharacterRosterHandle>,
+    rosters: Res<Assets<CharacterRoster>>,
+    mut selection: ResMut<CharacterSelection>,
+    mut profile: ResMut<Profile>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if actions.just_pressed(Action::Back) {
+        next_state.set(GameState::Menu);
+        return;
+    }
+    for card in focused_query.iter() {
+        selection.0 = card.0;
+    }
+
+    for ButtonPressed(entity) in pressed.read() {
+        if back_query.contains(*entity) {
+            next_state.set(GameState::Menu);
+            continue;
+        }
+        let (Ok(card), Some(roster)) = (card_query.get(*entity), rosters.get(&roster_handle.0)) else {
+            continue;
+        };
+        let Some(character) = roster.characters.get(card.0) else {
+            continue;
+        };
+        if character.is_unlocked(&profile) {
+            next_state.set(GameState::Playing);
+        } else if let Unlock::Coins(cost) = character.unlock {
+            if profile.coins >= cost {
+                profile.coins -= cost;
+                profile.unlocked.push(character.id.clone());
+                profile.save();
+            }
+        }
+    }
+}
+
+fn update_character_cards(
+    roster_handle: Res<CharacterRosterHandle>,
+    rosters: Res<Assets<CharacterRoster>>,
+    profile: Res<Profile>,
+    strings: Strings,
+    card_query: Query<Ref<CharacterCard>>,
+    mut status_query: Query<(&CardStatusText, &mut Text), Without<CoinsText>>,
+    mut coins_query: Query<&mut Text, (With<CoinsText>, Without<CardStatusText>)>,
+) {
+    let cards_added = card_query.iter().any(|card| card.is_added());
+    if !(cards_added || profile.is_changed() || strings.is_changed()) {
+        return;
+    }
+    let Some(roster) = rosters.get(&roster_handle.0) else {
+        return;
+    };
+
+    for (status, mut text) in status_query.iter_mut() {
+        let Some(character) = roster.characters.get(status.0) else {
+            continue;
+        };
+        **text = match character.unlock {
+            _ if character.is_unlocked(&profile) => String::new(),
+            Unlock::Coins(cost) => strings.plural("characters.locked_coins", cost, &[]),
+            Unlock::Achievement(achievement) => strings.format("characters.locked_achievement", &[("achievement", &achievement.description(&strings))]),
+            Unlock::Free => String::new(),
+        };
+    }
+
+    for mut text in coins_query.iter_mut() {
+        **text = strings.format("characters.coins", &[("coins", &profile.coins)]);
+    }
+}
+
+fn cleanup_character_select(
+    mut commands: Commands,
+    query: Query<Entity, With<CharacterSelectUI>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn apply_regeneration(
+    time: Res<Time>,
+    mut query: Query<(&mut Health, &Passive), (With<Player>, Without<Dead>)>,
+) {
+    for (mut health, passive) in query.iter_mut() {
+        if let Perk::Regeneration(rate) = passive.perk && health.current > 0.0 && health.current < health.max {
+            let heal = rate * passive.level as f32 * time.delta_secs();
+            health.current = (health.current + heal).min(health.max);
+        }
+    }
+}
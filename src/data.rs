@@ -0,0 +1,41 @@
+use std::marker::PhantomData;
+
+use bevy::asset::{AssetLoader, LoadContext, io::Reader};
+use bevy::prelude::*;
+use ron::extensions::Extensions;
+use serde::de::DeserializeOwned;
+
+/// Loads any deserializable asset from a RON file, e.g. `data/characters.ron`.
+///
+/// Every instance claims the `ron` extension, so these assets must be loaded through a typed
+/// handle (`asset_server.load::<T>(..)`), which picks the loader by asset type.
+///
+/// Optional fields can be written without `Some(..)`.
+pub struct RonAssetLoader<T>(PhantomData<fn() -> T>);
+
+impl<T> Default for RonAssetLoader<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: Asset + DeserializeOwned> AssetLoader for RonAssetLoader<T> {
+    type Asset = T;
+    type Settings = ();
+    type Error = BevyError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<T, BevyError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::Options::default().with_default_extension(Extensions::IMPLICIT_SOME).from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+}
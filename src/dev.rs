@@ -0,0 +1,466 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::str::FromStr;
+
+use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
+use bevy::ecs::system::SystemParam;
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::prelude::*;
+
+use crate::character::{SelectedCharacter, Weapon};
+use crate::enemy::{EnemyArchetypes, EnemyKind, spawn_enemy};
+use crate::feedback::DamageNumber;
+use crate::input::KeyboardCaptured;
+use crate::particles::Particle;
+use crate::physics::OnHit;
+use crate::progression::Experience;
+use crate::tileset::Tileset;
+use crate::widgets;
+use crate::{
+    BULLET_HIT_RADIUS, Bullet, CONTACT_RADIUS, DamageEvent, DamageSystems, Dead, Enemy, EnemyBullet, EnemySpawnTimer, GameState, Health,
+    Player, apply_damage, check_death,
+};
+
+// Dev Plugin
+/// Developer tools, only built with the `dev` feature: an overlay toggled with F3 and a
+/// console toggled with the backquote key. Players never see them, so their text isn't
+/// localized.
+pub struct DevPlugin;
+
+impl Plugin for DevPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<FrameTimeDiagnosticsPlugin>() {
+            app.add_plugins(FrameTimeDiagnosticsPlugin::default());
+        }
+        app.init_resource::<DevOverlay>()
+            .init_resource::<Console>()
+            .init_resource::<GodMode>()
+            .add_systems(Startup, spawn_dev_ui)
+            .add_systems(Update, (toggle_overlay, show_dev_ui, (update_overlay, draw_colliders).run_if(overlay_visible)))
+            .add_systems(Update, (toggle_console, type_command).chain().in_set(DamageSystems::Deal).run_if(in_state(GameState::Playing)))
+            .add_systems(Update, apply_god_mode.after(apply_damage).before(check_death))
+            .add_systems(OnExit(GameState::Playing), close_console);
+    }
+}
+
+/// Most enemies one `spawn` can add, so a typo doesn't freeze the game.
+const MAX_SPAWN: u32 = 200;
+
+/// Distance from the player at which `spawn` places enemies.
+const SPAWN_RING_RADIUS: f32 = 250.0;
+
+/// Lines of console output kept on screen.
+const HISTORY_LINES: usize = 10;
+
+/// Every console command and how to call it, shown by `help`.
+const USAGE: [(&str, &str); 7] = [
+    ("spawn", "spawn <archetype> [count] - spawn enemies around the player"),
+    ("god", "god - toggle invulnerability"),
+    ("give", "give <weapon> - swap the player's weapon, keeping its level"),
+    ("setlevel", "setlevel <level> - level up to it, taking each level's upgrade"),
+    ("timescale", "timescale <scale> - run game time faster or slower, up to 10"),
+    ("kill_all", "kill_all - kill every enemy, counting the kills"),
+    ("help", "help - list the commands"),
+];
+
+/// Whether the F3 overlay is showing.
+#[derive(Resource, Default)]
+struct DevOverlay(bool);
+
+/// Keeps the player's health full, set with `god`.
+#[derive(Resource, Default)]
+struct GodMode(bool);
+
+#[derive(Resource, Default)]
+struct Console {
+    open: bool,
+    input: String,
+    history: VecDeque<String>,
+}
+
+impl Console {
+    fn print(&mut self, text: &str) {
+        for line in text.lines() {
+            self.history.push_back(line.to_string());
+        }
+        while self.history.len() > HISTORY_LINES {
+            self.history.pop_front();
+        }
+    }
+}
+
+#[derive(Component)]
+struct OverlayText;
+
+#[derive(Component)]
+struct ConsoleUI;
+
+#[derive(Component)]
+struct ConsoleText;
+
+/// A parsed console line.
+#[derive(Clone, PartialEq, Debug)]
+enum ConsoleCommand {
+    Spawn { archetype: String, count: u32 },
+    God,
+    /// Weapon name, matched ignoring case and with `_` standing for spaces.
+    Give(String),
+    SetLevel(u32),
+    TimeScale(f32),
+    KillAll,
+    Help,
+}
+
+impl FromStr for ConsoleCommand {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let Some((&name, args)) = words.split_first() else {
+            return Err("type a command, or `help` to list them".to_string());
+        };
+        let command = match (name, args) {
+            ("spawn", [archetype]) => ConsoleCommand::Spawn { archetype: archetype.to_string(), count: 1 },
+            ("spawn", [archetype, count]) => ConsoleCommand::Spawn { archetype: archetype.to_string(), count: number(count)? },
+            ("god", []) => ConsoleCommand::God,
+            ("give", [_, ..]) => ConsoleCommand::Give(args.join(" ")),
+            ("setlevel", [level]) => ConsoleCommand::SetLevel(number(level)?),
+            ("timescale", [scale]) => ConsoleCommand::TimeScale(number(scale)?),
+            ("kill_all", []) => ConsoleCommand::KillAll,
+            ("help", []) => ConsoleCommand::Help,
+            _ => {
+                return Err(match USAGE.iter().find(|(command, _)| *command == name) {
+                    Some((_, usage)) => format!("usage: {usage}"),
+                    None => format!("unknown command `{name}`, try `help`"),
+                });
+            }
+        };
+
+        match command {
+            ConsoleCommand::Spawn { count, .. } if !(1..=MAX_SPAWN).contains(&count) => {
+                Err(format!("can spawn 1 to {MAX_SPAWN} enemies at a time"))
+            }
+            ConsoleCommand::SetLevel(0) => Err("levels start at 1".to_string()),
+            ConsoleCommand::TimeScale(scale) if !(scale > 0.0 && scale <= 10.0) => {
+                Err("time scale must be above 0 and at most 10".to_string())
+            }
+            command => Ok(command),
+        }
+    }
+}
+
+fn number<T: FromStr>(word: &str) -> Result<T, String> {
+    word.parse().map_err(|_| format!("`{word}` is not a valid number"))
+}
+
+fn overlay_visible(overlay: Res<DevOverlay>) -> bool {
+    overlay.0
+}
+
+fn spawn_dev_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = TextFont { font: asset_server.load(widgets::FONT), font_size: 14.0, ..default() };
+
+    commands.spawn((
+        Text::new(""),
+        font.clone(),
+        TextColor(Color::srgb(0.6, 1.0, 0.6)),
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(12.0),
+            top: Val::Percent(30.0),
+            padding: UiRect::all(Val::Px(6.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+        Visibility::Hidden,
+        ZIndex(1002),
+        OverlayText,
+    ));
+
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(0.0),
+            width: Val::Percent(100.0),
+            padding: UiRect::all(Val::Px(8.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+        Visibility::Hidden,
+        ZIndex(1003),
+        ConsoleUI,
+    )).with_children(|console| {
+        console.spawn((Text::new(""), font, TextColor(Color::WHITE), ConsoleText));
+    });
+}
+
+fn toggle_overlay(keys: Res<ButtonInput<KeyCode>>, mut overlay: ResMut<DevOverlay>) {
+    if keys.just_pressed(KeyCode::F3) {
+        overlay.0 = !overlay.0;
+    }
+}
+
+fn show_dev_ui(
+    overlay: Res<DevOverlay>,
+    console: Res<Console>,
+    mut captured: ResMut<KeyboardCaptured>,
+    mut overlay_query: Query<&mut Visibility, (With<OverlayText>, Without<ConsoleUI>)>,
+    mut console_query: Query<&mut Visibility, With<ConsoleUI>>,
+) {
+    let shown = |on: bool| if on { Visibility::Inherited } else { Visibility::Hidden };
+    for mut visibility in overlay_query.iter_mut() {
+        visibility.set_if_neq(shown(overlay.0));
+    }
+    for mut visibility in console_query.iter_mut() {
+        visibility.set_if_neq(shown(console.open));
+    }
+    // Only on a change, so the console doesn't release the keyboard from other screens
+    if console.is_changed() {
+        captured.set_if_neq(KeyboardCaptured(console.open));
+    }
+}
+
+fn update_overlay(
+    diagnostics: Res<DiagnosticsStore>,
+    spawn_timer: Res<EnemySpawnTimer>,
+    virtual_time: Res<Time<Virtual>>,
+    god_mode: Res<GodMode>,
+    entity_query: Query<Entity>,
+    player_query: Query<(), With<Player>>,
+    enemy_query: Query<&EnemyKind, (With<Enemy>, Without<Dead>)>,
+    bullet_query: Query<Has<EnemyBullet>, With<Bullet>>,
+    particle_query: Query<&Visibility, With<Particle>>,
+    number_query: Query<&Visibility, With<DamageNumber>>,
+    mut text_query: Query<&mut Text, With<OverlayText>>,
+) {
+    let smoothed = |path| diagnostics.get(path).and_then(|diagnostic| diagnostic.smoothed()).unwrap_or(0.0);
+    let mut kinds = BTreeMap::<&str, usize>::new();
+    for kind in enemy_query.iter() {
+        *kinds.entry(kind.0.as_str()).or_default() += 1;
+    }
+    let kinds: Vec<String> = kinds.iter().map(|(kind, count)| format!("{kind} {count}")).collect();
+    let enemy_bullets = bullet_query.iter().filter(|from_enemy| *from_enemy).count();
+    // Particles and damage numbers are pooled, so only the visible ones are in use
+    let in_use = |visibility: &&Visibility| **visibility != Visibility::Hidden;
+
+    let lines = [
+        format!("FPS {:.0} ({:.1} ms)", smoothed(&FrameTimeDiagnosticsPlugin::FPS), smoothed(&FrameTimeDiagnosticsPlugin::FRAME_TIME)),
+        format!("Spawn rate {:.2}/s", 1.0 / spawn_timer.0.duration().as_secs_f32().max(f32::EPSILON)),
+        format!("Time scale {:.2}  God {}", virtual_time.relative_speed(), if god_mode.0 { "on" } else { "off" }),
+        format!("Entities {}", entity_query.iter().count()),
+        format!("Player {}", player_query.iter().count()),
+        format!("Enemies {}: {}", enemy_query.iter().count(), kinds.join(", ")),
+        format!("Bullets {} player, {} enemy", bullet_query.iter().count() - enemy_bullets, enemy_bullets),
+        format!("Particles {}/{}", particle_query.iter().filter(in_use).count(), particle_query.iter().count()),
+        format!("Damage numbers {}/{}", number_query.iter().filter(in_use).count(), number_query.iter().count()),
+    ];
+    for mut text in text_query.iter_mut() {
+        **text = lines.join("\n");
+    }
+}
+
+/// Circles where hits are checked: contact damage around the player and bullet hits around
+/// every target.
+fn draw_colliders(
+    mut gizmos: Gizmos,
+    player_query: Query<&Transform, (With<Player>, Without<Dead>)>,
+    enemy_query: Query<&Transform, (With<Enemy>, Without<Dead>)>,
+) {
+    for transform in player_query.iter() {
+        let position = transform.translation.truncate();
+        gizmos.circle_2d(position, CONTACT_RADIUS, Color::srgb(1.0, 0.9, 0.0));
+        gizmos.circle_2d(position, BULLET_HIT_RADIUS, Color::srgb(0.0, 0.9, 1.0));
+    }
+    for transform in enemy_query.iter() {
+        gizmos.circle_2d(transform.translation.truncate(), BULLET_HIT_RADIUS, Color::srgb(1.0, 0.3, 0.3));
+    }
+}
+
+fn toggle_console(keys: Res<ButtonInput<KeyCode>>, mut console: ResMut<Console>) {
+    if keys.just_pressed(KeyCode::Backquote) || (console.open && keys.just_pressed(KeyCode::Escape)) {
+        console.open = !console.open;
+        console.input.clear();
+    }
+}
+
+fn close_console(mut console: ResMut<Console>) {
+    console.open = false;
+    console.input.clear();
+}
+
+fn type_command(
+    mut keyboard: MessageReader<KeyboardInput>,
+    mut console: ResMut<Console>,
+    mut cheats: Cheats,
+    mut text_query: Query<&mut Text, With<ConsoleText>>,
+) {
+    if !console.open {
+        keyboard.clear();
+        return;
+    }
+
+    for input in keyboard.read() {
+        if !input.state.is_pressed() {
+            continue;
+        }
+        match &input.logical_key {
+            Key::Enter => {
+                let line = std::mem::take(&mut console.input);
+                console.print(&format!("> {line}"));
+                let reply = line.parse().and_then(|command| cheats.run(command));
+                console.print(&reply.unwrap_or_else(|error| format!("error: {error}")));
+            }
+            Key::Backspace => {
+                console.input.pop();
+            }
+            Key::Space => console.input.push(' '),
+            // The backquote that opened the console arrives as typed text as well
+            Key::Character(chars) => console.input.extend(chars.chars().filter(|c| *c != '`' && !c.is_control())),
+            _ => {}
+        }
+    }
+
+    if console.is_changed() {
+        let mut lines: Vec<&str> = console.history.iter().map(String::as_str).collect();
+        let prompt = format!("> {}_", console.input);
+        lines.push(&prompt);
+        for mut text in text_query.iter_mut() {
+            **text = lines.join("\n");
+        }
+    }
+}
+
+/// What the console commands act on.
+#[derive(SystemParam)]
+struct Cheats<'w, 's> {
+    commands: Commands<'w, 's>,
+    tileset: Res<'w, Tileset>,
+    archetypes: EnemyArchetypes<'w>,
+    character: SelectedCharacter<'w>,
+    experience: ResMut<'w, Experience>,
+    god_mode: ResMut<'w, GodMode>,
+    virtual_time: ResMut<'w, Time<Virtual>>,
+    damage_events: MessageWriter<'w, DamageEvent>,
+    player_query: Query<'w, 's, (Entity, &'static Transform, Option<&'static Weapon>), (With<Player>, Without<Dead>)>,
+    enemy_query: Query<'w, 's, (Entity, &'static Transform, &'static Health), (With<Enemy>, Without<Dead>)>,
+}
+
+impl Cheats<'_, '_> {
+    /// Carries out a command, returning what to print.
+    fn run(&mut self, command: ConsoleCommand) -> Result<String, String> {
+        match command {
+            ConsoleCommand::Spawn { archetype, count } => {
+                let archetype = self.archetypes.get(&archetype).ok_or_else(|| format!("no enemy archetype `{archetype}`"))?;
+                let center = self.player_query.single().map_or(Vec2::ZERO, |(_, transform, _)| transform.translation.truncate());
+                // Spawned with their own rng so the run's sequence stays the same
+                let mut rng = rand::rng();
+                for i in 0..count {
+                    let angle = i as f32 / count as f32 * std::f32::consts::TAU;
+                    let position = center + Vec2::from_angle(angle) * SPAWN_RING_RADIUS;
+                    spawn_enemy(&mut self.commands, &self.tileset, &archetype, position, &mut rng);
+                }
+                Ok(format!("spawned {count} {}", archetype.id))
+            }
+            ConsoleCommand::God => {
+                self.god_mode.0 = !self.god_mode.0;
+                Ok(format!("god mode {}", if self.god_mode.0 { "on" } else { "off" }))
+            }
+            ConsoleCommand::Give(name) => {
+                let wanted = name.replace('_', " ");
+                let weapons = self.character.weapons();
+                let Some(stats) = weapons.iter().find(|stats| stats.name.eq_ignore_ascii_case(&wanted)) else {
+                    let names: Vec<String> = weapons.iter().map(|stats| stats.name.to_lowercase().replace(' ', "_")).collect();
+                    return Err(format!("no weapon `{name}`, try one of: {}", names.join(", ")));
+                };
+                let Ok((player, _, current)) = self.player_query.single() else {
+                    return Err("there is no player".to_string());
+                };
+                let mut weapon = Weapon::from(stats);
+                weapon.level = current.map_or(1, |current| current.level);
+                // Removed first so the HUD picks up the new icon
+                self.commands.entity(player).remove::<Weapon>().insert(weapon);
+                Ok(format!("gave {}", stats.name))
+            }
+            ConsoleCommand::SetLevel(level) => set_level(&mut self.experience, level),
+            ConsoleCommand::TimeScale(scale) => {
+                self.virtual_time.set_relative_speed(scale);
+                Ok(format!("time scale {scale}, until the game speed setting changes"))
+            }
+            ConsoleCommand::KillAll => {
+                let mut count = 0;
+                // Killed through damage so score, experience and death effects work as usual
+                for (enemy, transform, health) in self.enemy_query.iter() {
+                    self.damage_events.write(DamageEvent {
+                        target: enemy,
+                        amount: health.current,
+                        crit: false,
+                        source: transform.translation,
+                        on_hit: OnHit::default(),
+                        status: None,
+                    });
+                    count += 1;
+                }
+                Ok(format!("killed {count} enemies"))
+            }
+            ConsoleCommand::Help => Ok(USAGE.iter().map(|(_, usage)| *usage).collect::<Vec<_>>().join("\n")),
+        }
+    }
+}
+
+/// Raises the player to `level`. Levels only go up, since the upgrades taken on the way can't be
+/// handed back.
+fn set_level(experience: &mut Experience, level: u32) -> Result<String, String> {
+    if level < experience.level {
+        return Err(format!("already level {}, levels can't be lowered", experience.level));
+    }
+    if level > experience.level {
+        // Earned as experience, so every level on the way still grants its upgrade
+        experience.xp = (experience.level..level).map(|level| Experience { level, xp: 0 }.xp_to_next()).sum();
+    }
+    Ok(format!("level {level}"))
+}
+
+/// Runs between damage and the death check, so the player never reaches zero health.
+fn apply_god_mode(god_mode: Res<GodMode>, mut player_query: Query<&mut Health, (With<Player>, Without<Dead>)>) {
+    if !god_mode.0 {
+        return;
+    }
+    for mut health in player_query.iter_mut() {
+        health.current = health.max;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_commands_and_rejects_bad_arguments() {
+        let parse = |line: &str| line.parse::<ConsoleCommand>();
+        assert_eq!(parse("spawn demon 5"), Ok(ConsoleCommand::Spawn { archetype: "demon".into(), count: 5 }));
+        assert_eq!(parse("  spawn snake "), Ok(ConsoleCommand::Spawn { archetype: "snake".into(), count: 1 }));
+        assert_eq!(parse("give Hand Axe"), Ok(ConsoleCommand::Give("Hand Axe".into())));
+        assert_eq!(parse("setlevel 7"), Ok(ConsoleCommand::SetLevel(7)));
+        assert_eq!(parse("timescale 0.5"), Ok(ConsoleCommand::TimeScale(0.5)));
+        assert_eq!(parse("kill_all"), Ok(ConsoleCommand::KillAll));
+
+        assert!(parse("").is_err());
+        assert!(parse("fly").is_err());
+        assert!(parse("spawn demon many").is_err());
+        assert!(parse("spawn demon 0").is_err());
+        assert!(parse("setlevel 0").is_err());
+        assert!(parse("timescale 0").is_err());
+        assert!(parse("timescale NaN").is_err());
+        assert_eq!(parse("god mode"), Err(format!("usage: {}", USAGE[1].1)));
+    }
+
+    #[test]
+    fn setlevel_earns_the_levels_and_refuses_to_go_down() {
+        let mut experience = Experience { level: 3, xp: 4 };
+        assert_eq!(set_level(&mut experience, 5), Ok("level 5".to_string()));
+        assert_eq!(experience, Experience { level: 3, xp: 15 + 20 });
+
+        let mut experience = Experience { level: 6, xp: 4 };
+        assert!(set_level(&mut experience, 2).is_err());
+        assert_eq!(experience, Experience { level: 6, xp: 4 });
+    }
+}
@@ -0,0 +1,211 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::animation::{Animations, Animator};
+use crate::data::RonAssetLoader;
+use crate::physics::{Mass, Velocity};
+use crate::status::{StatusImmunity, StatusKind};
+use crate::tileset::{TileRef, Tileset};
+use crate::{Damage, Enemy, EnemySpeed, Health, HealthBar, LastDirection, ShootingEnemy};
+
+// Enemy Plugin
+pub struct EnemyPlugin;
+
+impl Plugin for EnemyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<EnemyRoster>()
+            .register_asset_loader(RonAssetLoader::<EnemyRoster>::default())
+            .add_systems(Startup, load_enemies);
+    }
+}
+
+/// Every kind of enemy that can spawn, loaded from `assets/data/enemies.ron`.
+#[derive(Asset, TypePath, Deserialize)]
+pub struct EnemyRoster {
+    pub archetypes: Vec<EnemyArchetype>,
+}
+
+impl Default for EnemyRoster {
+    /// The original three enemies, used if the roster asset fails to load.
+    fn default() -> Self {
+        Self {
+            archetypes: vec![
+                EnemyArchetype {
+                    id: "snake".to_string(),
+                    sprite: "snake".into(),
+                    health: 50.0,
+                    damage: 10.0,
+                    speed: (40.0, 60.0),
+                    mass: 1.0,
+                    shoot_interval: None,
+                    weight: 1.0,
+                    immune: vec![StatusKind::Poison],
+                    animations: Animations::default(),
+                },
+                EnemyArchetype {
+                    id: "demon".to_string(),
+                    sprite: "demon".into(),
+                    health: 50.0,
+                    damage: 15.0,
+                    speed: (60.0, 80.0),
+                    mass: 3.0,
+                    shoot_interval: None,
+                    weight: 1.0,
+                    immune: vec![StatusKind::Burn],
+                    animations: Animations::default(),
+                },
+                EnemyArchetype {
+                    id: "shooter".to_string(),
+                    sprite: "cultist".into(),
+                    health: 50.0,
+                    damage: 5.0,
+                    speed: (30.0, 40.0),
+                    mass: 1.5,
+                    shoot_interval: Some(2.0),
+                    weight: 1.0,
+                    immune: vec![],
+                    animations: Animations::default(),
+                },
+            ],
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+pub struct EnemyArchetype {
+    pub id: String,
+    pub sprite: TileRef,
+    pub health: f32,
+    /// Contact damage dealt to the player.
+    pub damage: f32,
+    /// Movement speed is picked at random from this range.
+    pub speed: (f32, f32),
+    /// Knockback is divided by mass, so heavy enemies barely budge.
+    #[serde(default = "default_mass")]
+    pub mass: f32,
+    /// Seconds between shots, for enemies that shoot at the player.
+    #[serde(default)]
+    pub shoot_interval: Option<f32>,
+    /// Relative chance of being picked by the spawner.
+    #[serde(default = "default_weight")]
+    pub weight: f32,
+    /// Status effects this enemy can't be afflicted with.
+    #[serde(default)]
+    pub immune: Vec<StatusKind>,
+    /// A shooter with an attack clip fires on the clip's "fire" event instead of right away.
+    #[serde(default)]
+    pub animations: Animations,
+}
+
+fn default_mass() -> f32 {
+    1.0
+}
+
+fn default_weight() -> f32 {
+    1.0
+}
+
+/// Handle to the roster. A default handle never loads, leaving the built-in archetypes.
+#[derive(Resource, Default)]
+pub struct EnemyRosterHandle(Handle<EnemyRoster>);
+
+/// Read access to the loaded enemy archetypes, falling back to the built-in ones.
+#[derive(SystemParam)]
+pub struct EnemyArchetypes<'w> {
+    handle: Res<'w, EnemyRosterHandle>,
+    rosters: Res<'w, Assets<EnemyRoster>>,
+}
+
+impl EnemyArchetypes<'_> {
+    pub fn get(&self, id: &str) -> Option<EnemyArchetype> {
+        match self.rosters.get(&self.handle.0) {
+            Some(roster) => roster.archetypes.iter().find(|archetype| archetype.id == id).cloned(),
+            None => EnemyRoster::default().archetypes.into_iter().find(|archetype| archetype.id == id),
+        }
+    }
+
+    /// Picks an archetype at random, weighted by `EnemyArchetype::weight`.
+    pub fn random(&self, rng: &mut impl Rng) -> EnemyArchetype {
+        let archetypes = match self.rosters.get(&self.handle.0) {
+            Some(roster) if !roster.archetypes.is_empty() => roster.archetypes.clone(),
+            _ => EnemyRoster::default().archetypes,
+        };
+
+        let total: f32 = archetypes.iter().map(|archetype| archetype.weight.max(0.0)).sum();
+        let mut roll = rng.random::<f32>() * total;
+        for archetype in &archetypes {
+            roll -= archetype.weight.max(0.0);
+            if roll < 0.0 {
+                return archetype.clone();
+            }
+        }
+        archetypes[archetypes.len() - 1].clone()
+    }
+}
+
+/// Id of the archetype an enemy was spawned from.
+#[derive(Component)]
+pub struct EnemyKind(pub String);
+
+fn load_enemies(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(EnemyRosterHandle(asset_server.load("data/enemies.ron")));
+}
+
+/// Spawns an enemy of the given archetype, complete with its health bar.
+pub fn spawn_enemy(commands: &mut Commands, tileset: &Tileset, archetype: &EnemyArchetype, position: Vec2, rng: &mut impl Rng) -> Entity {
+    let speed = if archetype.speed.0 < archetype.speed.1 {
+        rng.random_range(archetype.speed.0..archetype.speed.1)
+    } else {
+        archetype.speed.0
+    };
+
+    let mut entity = commands.spawn((
+        tileset.sprite(tileset.index(&archetype.sprite)),
+        Transform::from_xyz(position.x, position.y, 0.0).with_scale(Vec3::splat(4.0)),
+        Enemy,
+        EnemyKind(archetype.id.clone()),
+        Health { current: archetype.health, max: archetype.health },
+        Damage(archetype.damage),
+        EnemySpeed(speed),
+        LastDirection(Vec3::X),
+        Animator::new(tileset, &archetype.animations, &archetype.sprite),
+        Velocity::default(),
+        Mass(archetype.mass),
+    ));
+
+    if !archetype.immune.is_empty() {
+        entity.insert(StatusImmunity(archetype.immune.clone()));
+    }
+
+    if let Some(interval) = archetype.shoot_interval {
+        entity.insert(ShootingEnemy {
+            shoot_timer: Timer::from_seconds(interval, TimerMode::Repeating),
+        });
+    }
+
+    entity.with_children(|parent| {
+        // Health bar background
+        parent.spawn((
+            Sprite {
+                color: Color::srgb(0.3, 0.3, 0.3),
+                custom_size: Some(Vec2::new(12.0, 1.5)),
+                ..default()
+            },
+            Transform::from_xyz(0.0, 8.0, 0.0),
+        ));
+        // Health bar foreground
+        parent.spawn((
+            Sprite {
+                color: Color::srgb(0.8, 0.0, 0.0),
+                custom_size: Some(Vec2::new(12.0, 1.5)),
+                ..default()
+            },
+            Transform::from_xyz(0.0, 8.0, 0.1),
+            HealthBar,
+        ));
+    });
+
+    entity.id()
+}
@@ -0,0 +1,216 @@
+use bevy::prelude::*;
+
+use crate::{DamageDealt, GameState, Health, widgets};
+
+// Feedback Plugin
+pub struct FeedbackPlugin;
+
+impl Plugin for FeedbackPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FeedbackSettings>()
+            .init_resource::<DamageNumberPool>()
+            .add_systems(Update, (spawn_hit_feedback, update_damage_numbers, update_hit_flash, apply_tint).chain())
+            .add_systems(OnEnter(GameState::Menu), release_damage_numbers);
+    }
+}
+
+/// Tuning for the hit feedback shown whenever something takes damage.
+#[derive(Resource)]
+pub struct FeedbackSettings {
+    pub damage_numbers: bool,
+    /// Seconds a damage number stays on screen.
+    pub number_lifetime: f32,
+    /// Upward speed of a damage number in pixels per second.
+    pub number_rise_speed: f32,
+    pub number_color: Color,
+    pub crit_color: Color,
+    /// Color used for damage taken by the player.
+    pub player_color: Color,
+    pub number_size: f32,
+    pub crit_size: f32,
+    /// Most damage numbers alive at once; the oldest one is reused past this.
+    pub max_numbers: usize,
+    pub flash: bool,
+    pub flash_duration: f32,
+    pub flash_color: Color,
+}
+
+impl Default for FeedbackSettings {
+    fn default() -> Self {
+        Self {
+            damage_numbers: true,
+            number_lifetime: 0.7,
+            number_rise_speed: 60.0,
+            number_color: Color::WHITE,
+            crit_color: Color::srgb(1.0, 0.85, 0.1),
+            player_color: Color::srgb(1.0, 0.25, 0.25),
+            number_size: 18.0,
+            crit_size: 26.0,
+            max_numbers: 64,
+            flash: true,
+            flash_duration: 0.08,
+            // Values above 1.0 push the sprite towards white
+            flash_color: Color::linear_rgb(8.0, 8.0, 8.0),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct DamageNumber {
+    timer: Timer,
+}
+
+/// Damage number entities are reused instead of being spawned for every hit.
+#[derive(Resource, Default)]
+pub struct DamageNumberPool {
+    free: Vec<Entity>,
+    /// Numbers currently on screen, oldest first.
+    active: Vec<Entity>,
+}
+
+/// Briefly tints a sprite after it takes damage.
+#[derive(Component)]
+pub struct HitFlash {
+    timer: Timer,
+}
+
+/// Resting color of a damageable sprite. Without one it is drawn untinted.
+#[derive(Component)]
+pub struct Tint(pub Color);
+
+fn spawn_hit_feedback(
+    mut commands: Commands,
+    mut damage_dealt: MessageReader<DamageDealt>,
+    settings: Res<FeedbackSettings>,
+    mut pool: ResMut<DamageNumberPool>,
+    mut sprite_query: Query<(&mut Sprite, Option<&mut HitFlash>), With<Health>>,
+    mut number_query: Query<(&mut DamageNumber, &mut Transform, &mut Text2d, &mut TextFont, &mut TextColor, &mut Visibility)>,
+    asset_server: Res<AssetServer>,
+) {
+    for hit in damage_dealt.read() {
+        if settings.damage_numbers {
+            let amount = hit.amount.round() as i32;
+            let text = if hit.crit { format!("{amount}!") } else { format!("{amount}") };
+            let (color, size) = if hit.to_player {
+                (settings.player_color, settings.number_size)
+            } else if let Some(status) = hit.status {
+                (status.tint(), settings.number_size)
+            } else if hit.crit {
+                (settings.crit_color, settings.crit_size)
+            } else {
+                (settings.number_color, settings.number_size)
+            };
+            // Spread numbers out a little so rapid hits don't stack on top of each other
+            let jitter = Vec3::new(rand::random_range(-12.0..12.0), 0.0, 0.0);
+            let translation = hit.position + Vec3::new(0.0, 30.0, 50.0) + jitter;
+
+            let reused = match pool.free.pop() {
+                Some(entity) => Some(entity),
+                None if pool.active.len() >= settings.max_numbers => Some(pool.active.remove(0)),
+                None => None,
+            };
+            let timer = Timer::from_seconds(settings.number_lifetime, TimerMode::Once);
+
+            if let Some(entity) = reused
+                && let Ok((mut number, mut transform, mut text2d, mut font, mut text_color, mut visibility)) = number_query.get_mut(entity)
+            {
+                number.timer = timer;
+                transform.translation = translation;
+                text2d.0 = text;
+                font.font_size = size;
+                text_color.0 = color;
+                *visibility = Visibility::Visible;
+                pool.active.push(entity);
+            } else {
+                let entity = commands
+                    .spawn((
+                        DamageNumber { timer },
+                        Text2d::new(text),
+                        TextFont {
+                            font: asset_server.load(widgets::FONT),
+                            font_size: size,
+                            ..default()
+                        },
+                        TextColor(color),
+                        Transform::from_translation(translation),
+                        Visibility::Visible,
+                    ))
+                    .id();
+                pool.active.push(entity);
+            }
+        }
+
+        if settings.flash {
+            if let Ok((mut sprite, flash)) = sprite_query.get_mut(hit.target) {
+                match flash {
+                    Some(mut flash) => flash.timer.reset(),
+                    None => {
+                        commands.entity(hit.target).try_insert(HitFlash {
+                            timer: Timer::from_seconds(settings.flash_duration, TimerMode::Once),
+                        });
+                    }
+                }
+                sprite.color = settings.flash_color;
+            }
+        }
+    }
+}
+
+fn update_damage_numbers(
+    time: Res<Time>,
+    settings: Res<FeedbackSettings>,
+    mut pool: ResMut<DamageNumberPool>,
+    mut number_query: Query<(Entity, &mut DamageNumber, &mut Transform, &mut TextColor, &mut Visibility)>,
+) {
+    for (entity, mut number, mut transform, mut color, mut visibility) in number_query.iter_mut() {
+        if *visibility == Visibility::Hidden {
+            continue;
+        }
+
+        number.timer.tick(time.delta());
+        transform.translation.y += settings.number_rise_speed * time.delta_secs();
+        color.0.set_alpha(number.timer.fraction_remaining());
+
+        if number.timer.is_finished() {
+            *visibility = Visibility::Hidden;
+            pool.active.retain(|&active| active != entity);
+            pool.free.push(entity);
+        }
+    }
+}
+
+fn update_hit_flash(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut flash_query: Query<(Entity, &mut HitFlash)>,
+) {
+    for (entity, mut flash) in flash_query.iter_mut() {
+        flash.timer.tick(time.delta());
+        if flash.timer.is_finished() {
+            commands.entity(entity).try_remove::<HitFlash>();
+        }
+    }
+}
+
+/// Keeps damageable sprites at their resting color whenever they aren't flashing.
+fn apply_tint(mut sprite_query: Query<(&mut Sprite, Option<&Tint>), (With<Health>, Without<HitFlash>)>) {
+    for (mut sprite, tint) in sprite_query.iter_mut() {
+        let color = tint.map_or(Color::WHITE, |tint| tint.0);
+        if sprite.color != color {
+            sprite.color = color;
+        }
+    }
+}
+
+fn release_damage_numbers(
+    mut pool: ResMut<DamageNumberPool>,
+    mut number_query: Query<(Entity, &mut Visibility), With<DamageNumber>>,
+) {
+    for (entity, mut visibility) in number_query.iter_mut() {
+        *visibility = Visibility::Hidden;
+        if !pool.free.contains(&entity) {
+            pool.free.push(entity);
+        }
+    }
+    pool.active.clear();
+}
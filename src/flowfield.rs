@@ -0,0 +1,169 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::world::{WalkabilityGrid, tile_at, tile_center};
+use crate::{Dead, GameState, Player};
+
+// Flow Field Plugin
+pub struct FlowFieldPlugin;
+
+impl Plugin for FlowFieldPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FlowField>()
+            .add_systems(FixedUpdate, update_flow_field.run_if(in_state(GameState::Playing)))
+            .add_systems(OnEnter(GameState::Menu), reset_flow_field);
+    }
+}
+
+/// Tiles expanded per fixed step, so each rebuild is spread over a few steps instead of
+/// stalling one frame. Enemies keep following the previous field, one tile behind, meanwhile.
+const TILES_PER_STEP: usize = 2048;
+const UNREACHABLE: u16 = u16::MAX;
+
+const NEIGHBORS: [IVec2; 8] = [
+    IVec2::new(1, 0),
+    IVec2::new(-1, 0),
+    IVec2::new(0, 1),
+    IVec2::new(0, -1),
+    IVec2::new(1, 1),
+    IVec2::new(1, -1),
+    IVec2::new(-1, 1),
+    IVec2::new(-1, -1),
+];
+
+/// Distances in tiles to the player over the walkability grid. Enemies follow the distances
+/// downhill, which routes them around obstacles for the cost of a few lookups each.
+#[derive(Default)]
+struct Distances {
+    origin: IVec2,
+    size: IVec2,
+    walkable: Vec<bool>,
+    distance: Vec<u16>,
+}
+
+impl Distances {
+    fn index(&self, tile: IVec2) -> Option<usize> {
+        let local = tile - self.origin;
+        (local.cmpge(IVec2::ZERO).all() && local.cmplt(self.size).all()).then(|| (local.y * self.size.x + local.x) as usize)
+    }
+
+    fn distance(&self, tile: IVec2) -> u16 {
+        self.index(tile).map_or(UNREACHABLE, |index| self.distance[index])
+    }
+
+    fn is_walkable(&self, tile: IVec2) -> bool {
+        self.index(tile).is_some_and(|index| self.walkable[index])
+    }
+
+    /// Diagonal steps are only allowed when they don't clip the corner of an obstacle.
+    fn can_step(&self, from: IVec2, offset: IVec2) -> bool {
+        self.is_walkable(from + offset)
+            && (offset.x == 0 || offset.y == 0
+                || (self.is_walkable(from + IVec2::new(offset.x, 0)) && self.is_walkable(from + IVec2::new(0, offset.y))))
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct FlowField {
+    /// The last finished field, which enemies sample.
+    current: Distances,
+    /// A field being built breadth-first over several steps. Its buffers are the ones the
+    /// field before `current` used, so rebuilding doesn't allocate.
+    pending: Distances,
+    queue: VecDeque<usize>,
+    target: Option<IVec2>,
+    building: bool,
+}
+
+impl FlowField {
+    /// Direction to move in from `position` to get closer to the player, or `None` if the position
+    /// isn't covered by the field, can't reach the player, or is already on the player's tile.
+    pub fn direction(&self, position: Vec2) -> Option<Vec2> {
+        let field = &self.current;
+        let tile = tile_at(position);
+        let here = field.distance(tile);
+        if here == UNREACHABLE || here == 0 {
+            return None;
+        }
+
+        let (best, _) = NEIGHBORS
+            .iter()
+            .filter(|offset| field.can_step(tile, **offset))
+            .map(|offset| (tile + *offset, field.distance(tile + *offset)))
+            .filter(|(_, distance)| *distance < here)
+            .min_by_key(|(_, distance)| *distance)?;
+        Some((tile_center(best) - position).normalize_or_zero())
+    }
+}
+
+/// Rebuilds the field from scratch whenever the player changes tile or the grid changes,
+/// advancing the breadth-first search by a fixed budget each step and publishing it once it is
+/// done.
+fn update_flow_field(
+    mut flow: ResMut<FlowField>,
+    grid: Res<WalkabilityGrid>,
+    player_query: Query<&Transform, (With<Player>, Without<Dead>)>,
+) {
+    let Ok(player_transform) = player_query.single() else {
+        return;
+    };
+    let target = tile_at(player_transform.translation.truncate());
+    let flow = &mut *flow;
+
+    if flow.target != Some(target) || grid.is_changed() {
+        flow.target = Some(target);
+        let (origin, size) = (grid.origin(), grid.size());
+        let pending = &mut flow.pending;
+        pending.origin = origin;
+        pending.size = size;
+        pending.walkable.clear();
+        for y in 0..size.y {
+            for x in 0..size.x {
+                pending.walkable.push(grid.is_walkable(origin + IVec2::new(x, y)));
+            }
+        }
+        pending.distance.clear();
+        pending.distance.resize(pending.walkable.len(), UNREACHABLE);
+        flow.queue.clear();
+        if let Some(index) = flow.pending.index(target) {
+            flow.pending.distance[index] = 0;
+            flow.queue.push_back(index);
+        }
+        flow.building = true;
+    }
+
+    if !flow.building {
+        return;
+    }
+
+    let field = &mut flow.pending;
+    for _ in 0..TILES_PER_STEP {
+        let Some(index) = flow.queue.pop_front() else {
+            break;
+        };
+        let tile = field.origin + IVec2::new(index as i32 % field.size.x, index as i32 / field.size.x);
+        let next = field.distance[index] + 1;
+
+        for offset in NEIGHBORS {
+            if !field.can_step(tile, offset) {
+                continue;
+            }
+            if let Some(neighbor) = field.index(tile + offset) {
+                if field.distance[neighbor] == UNREACHABLE {
+                    field.distance[neighbor] = next;
+                    flow.queue.push_back(neighbor);
+                }
+            }
+        }
+    }
+
+    if flow.queue.is_empty() {
+        std::mem::swap(&mut flow.current, &mut flow.pending);
+        flow.building = false;
+    }
+}
+
+fn reset_flow_field(mut flow: ResMut<FlowField>) {
+    *flow = FlowField::default();
+}
@@ -0,0 +1,337 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::input::{Action, ActionState};
+use crate::locale::{Localized, Strings};
+use crate::storage;
+use crate::widgets::{self, ButtonPressed, FocusButton};
+use crate::{GameOverText, GameState, RunStats, Score};
+
+const MAX_ENTRIES: usize = 10;
+const MAX_NAME_LEN: usize = 12;
+
+// High Score Plugin
+pub struct HighScorePlugin;
+
+impl Plugin for HighScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(HighScoreTable::load())
+            .add_systems(OnEnter(GameState::HighScores), setup_high_scores)
+            .add_systems(Update, high_scores_input.run_if(in_state(GameState::HighScores)))
+            .add_systems(OnExit(GameState::HighScores), cleanup_high_scores)
+            .add_systems(OnEnter(GameState::GameOver), submit_run)
+            // Leaving is checked before name entry, so the key confirming a name doesn't also press
+            // the menu button that replaces the prompt
+            .add_systems(Update, (game_over_input, enter_name).chain().run_if(in_state(GameState::GameOver)))
+            .add_systems(OnExit(GameState::GameOver), cleanup_game_over);
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HighScoreEntry {
+    pub name: String,
+    pub score: u32,
+    pub survival_time: f32,
+    pub kills: u32,
+    pub character: String,
+    pub seed: u64,
+    /// Seconds since the unix epoch.
+    pub date: u64,
+}
+
+#[derive(Resource, Serialize, Deserialize, Default, Debug)]
+pub struct HighScoreTable {
+    pub entries: Vec<HighScoreEntry>,
+}
+
+impl HighScoreTable {
+    const FILE: &'static str = "highscores.ron";
+
+    pub fn load() -> Self {
+        let mut table: Self = storage::load(Self::FILE);
+        table.sort();
+        table
+    }
+
+    pub fn save(&self) {
+        storage::save(Self::FILE, self);
+    }
+
+    /// Whether a run with this score would make it onto the table.
+    pub fn qualifies(&self, score: u32) -> bool {
+        score > 0 && (self.entries.len() < MAX_ENTRIES || self.entries.iter().any(|e| score > e.score))
+    }
+
+    /// Inserts the entry, keeping the table sorted and trimmed, and returns its rank.
+    pub fn insert(&mut self, entry: HighScoreEntry) -> Option<usize> {
+        let rank = self.entries.iter().position(|e| entry.score > e.score).unwrap_or(self.entries.len());
+        if rank >= MAX_ENTRIES {
+            return None;
+        }
+        self.entries.insert(rank, entry);
+        self.entries.truncate(MAX_ENTRIES);
+        Some(rank)
+    }
+
+    fn sort(&mut self) {
+        self.entries.sort_by(|a, b| b.score.cmp(&a.score).then(b.survival_time.total_cmp(&a.survival_time)));
+        self.entries.truncate(MAX_ENTRIES);
+    }
+}
+
+/// A record waiting for the player to type their name on the game over screen.
+#[derive(Resource)]
+struct PendingRecord {
+    entry: HighScoreEntry,
+    name: String,
+}
+
+/// Rank of the last inserted record, highlighted in the table.
+#[derive(Resource)]
+struct LatestRank(usize);
+
+#[derive(Component)]
+struct HighScoreUI;
+
+#[derive(Component)]
+struct HighScoreText;
+
+#[derive(Component)]
+struct NameEntryText;
+
+/// Shown once there is no name left to enter.
+#[derive(Component)]
+struct GameOverMenuButton;
+
+fn submit_run(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    score: Res<Score>,
+    stats: Res<RunStats>,
+    table: Res<HighScoreTable>,
+    strings: Strings,
+) {
+    let date = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let entry = HighScoreEntry {
+        name: String::new(),
+        score: score.0,
+        survival_time: stats.survival_time,
+        kills: stats.kills,
+        character: stats.character.clone(),
+        seed: stats.seed,
+        date,
+    };
+
+    let font = asset_server.load(widgets::FONT);
+    let qualifies = table.qualifies(entry.score);
+    if qualifies {
+        commands.insert_resource(PendingRecord { entry, name: String::new() });
+    }
+
+    // Leaves room at the top for the game over title
+    let root = Node {
+        padding: UiRect::top(Val::Percent(10.0)),
+        ..widgets::screen()
+    };
+    commands.spawn((root, HighScoreUI, ZIndex(1001))).with_children(|parent| {
+        if qualifies {
+            parent.spawn((
+                Localized::new("high_scores.new_record").with("name", ""),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 24.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                NameEntryText,
+            ));
+        }
+        parent.spawn((
+            Text::new(format_table(&table, None, &strings)),
+            TextFont {
+                font: font.clone(),
+                font_size: 18.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+            HighScoreText,
+        ));
+        if !qualifies {
+            parent.spawn((widgets::button(&font, "game_over.to_menu"), GameOverMenuButton));
+        }
+    });
+}
+
+fn enter_name(
+    mut commands: Commands,
+    mut keyboard: MessageReader<KeyboardInput>,
+    pending: Option<ResMut<PendingRecord>>,
+    mut table: ResMut<HighScoreTable>,
+    asset_server: Res<AssetServer>,
+    strings: Strings,
+    root_query: Query<Entity, With<HighScoreUI>>,
+    mut prompt_query: Query<(Entity, &mut Localized), With<NameEntryText>>,
+    mut table_query: Query<&mut Text, With<HighScoreText>>,
+) {
+    let Some(mut pending) = pending else {
+        keyboard.clear();
+        return;
+    };
+
+    let mut confirmed = false;
+    for input in keyboard.read() {
+        if !input.state.is_pressed() {
+            continue;
+        }
+        match &input.logical_key {
+            Key::Enter => confirmed = true,
+            Key::Backspace => {
+                pending.name.pop();
+            }
+            Key::Character(chars) => {
+                for c in chars.chars().filter(|c| c.is_ascii_alphanumeric()) {
+                    if pending.name.len() < MAX_NAME_LEN {
+                        pending.name.push(c.to_ascii_uppercase());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if confirmed {
+        let mut entry = pending.entry.clone();
+        entry.name = if pending.name.is_empty() { strings.get("high_scores.default_name") } else { pending.name.clone() };
+        let rank = table.insert(entry);
+        table.save();
+        commands.remove_resource::<PendingRecord>();
+
+        // The name is in the table now, so the prompt gives way to the way out
+        for (entity, _) in prompt_query.iter() {
+            commands.entity(entity).despawn();
+        }
+        let font = asset_server.load(widgets::FONT);
+        for root in root_query.iter() {
+            commands.entity(root).with_child((widgets::button(&font, "game_over.to_menu"), GameOverMenuButton));
+        }
+        for mut text in table_query.iter_mut() {
+            **text = format_table(&table, rank, &strings);
+        }
+        if let Some(rank) = rank {
+            commands.insert_resource(LatestRank(rank));
+        }
+    } else if pending.is_changed() {
+        for (_, mut text) in prompt_query.iter_mut() {
+            *text = Localized::new("high_scores.new_record").with("name", &pending.name);
+        }
+    }
+}
+
+fn game_over_input(
+    mut pressed: MessageReader<ButtonPressed>,
+    button_query: Query<(), With<GameOverMenuButton>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if pressed.read().any(|ButtonPressed(entity)| button_query.contains(*entity)) {
+        next_state.set(GameState::Menu);
+    }
+}
+
+fn cleanup_game_over(
+    mut commands: Commands,
+    query: Query<Entity, Or<(With<HighScoreUI>, With<GameOverText>)>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+    commands.remove_resource::<PendingRecord>();
+}
+
+#[derive(Component)]
+struct HighScoresScreen;
+
+fn setup_high_scores(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    table: Res<HighScoreTable>,
+    latest: Option<Res<LatestRank>>,
+    strings: Strings,
+) {
+    let font = asset_server.load(widgets::FONT);
+    commands.spawn((widgets::screen(), HighScoresScreen)).with_children(|parent| {
+        parent.spawn((
+            Text::new(format_table(&table, latest.map(|rank| rank.0), &strings)),
+            TextFont {
+                font: font.clone(),
+                font_size: 16.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.8, 0.8, 0.8)),
+        ));
+        parent.spawn(widgets::button(&font, "common.back"));
+    });
+}
+
+fn high_scores_input(
+    actions: Res<ActionState>,
+    mut pressed: MessageReader<ButtonPressed>,
+    button_query: Query<(), With<FocusButton>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    // The only button is Back
+    if actions.just_pressed(Action::Back) || pressed.read().any(|ButtonPressed(entity)| button_query.contains(*entity)) {
+        next_state.set(GameState::Menu);
+    }
+}
+
+fn cleanup_high_scores(mut commands: Commands, query: Query<Entity, With<HighScoresScreen>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn format_table(table: &HighScoreTable, highlight: Option<usize>, strings: &Strings) -> String {
+    let mut text = format!("{}\n\n", strings.get("high_scores.title"));
+    if table.entries.is_empty() {
+        text.push_str(&strings.get("high_scores.empty"));
+        return text;
+    }
+
+    text.push_str(&strings.get("high_scores.header"));
+    text.push('\n');
+    for (i, entry) in table.entries.iter().enumerate() {
+        let marker = if highlight == Some(i) { '>' } else { ' ' };
+        let secs = entry.survival_time as u32;
+        text.push_str(&format!(
+            "{marker}{:>2} {:<12} {:>6} {:>2}:{:02} {:>5} {:<10} {:08x}  {}\n",
+            i + 1,
+            entry.name,
+            entry.score,
+            secs / 60,
+            secs % 60,
+            entry.kills,
+            entry.character,
+            entry.seed as u32,
+            format_date(entry.date),
+        ));
+    }
+    text
+}
+
+/// Formats a unix timestamp as `YYYY-MM-DD` (UTC).
+fn format_date(timestamp: u64) -> String {
+    // Howard Hinnant's days-to-civil algorithm
+    let days = (timestamp / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let doe = days.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
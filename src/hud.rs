@@ -0,0 +1,430 @@
+use bevy::prelude::*;
+use bevy::render::render_resource::AsBindGroup;
+use bevy::shader::ShaderRef;
+
+use crate::accessibility::AccessibilitySettings;
+use crate::character::{Passive, Weapon};
+use crate::locale::Localized;
+use crate::progression::{Experience, LevelUp};
+use crate::tileset::Tileset;
+use crate::{GameState, Health, Player, RunStats, Score, widgets};
+
+// HUD Plugin
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(UiMaterialPlugin::<CooldownMaterial>::default())
+            .add_systems(OnEnter(GameState::Playing), setup_hud)
+            .add_systems(
+                Update,
+                (
+                    update_health_bar,
+                    update_timer,
+                    update_score_and_kills,
+                    update_experience,
+                    set_slot_icons,
+                    update_weapon_slot,
+                    update_passive_slot,
+                    show_level_up,
+                )
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(OnExit(GameState::Playing), cleanup_hud);
+    }
+}
+
+/// Radial overlay that darkens the part of a slot still on cooldown.
+#[derive(AsBindGroup, Asset, TypePath, Debug, Clone)]
+pub struct CooldownMaterial {
+    /// Only `x` is read: the fraction of the cooldown remaining. A `Vec4` keeps the uniform
+    /// 16-byte aligned for webgl2.
+    #[uniform(0)]
+    progress: Vec4,
+    #[uniform(1)]
+    color: Vec4,
+}
+
+impl UiMaterial for CooldownMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/cooldown.wgsl".into()
+    }
+}
+
+#[derive(Component)]
+struct Hud;
+
+#[derive(Component)]
+struct HealthFill;
+
+#[derive(Component)]
+struct HealthLabel;
+
+#[derive(Component)]
+struct ExperienceFill;
+
+#[derive(Component)]
+struct LevelText;
+
+#[derive(Component)]
+struct TimerText;
+
+#[derive(Component)]
+struct ScoreText;
+
+#[derive(Component)]
+struct KillsText;
+
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+enum Slot {
+    Weapon,
+    Passive,
+}
+
+#[derive(Component)]
+struct SlotIcon(Slot);
+
+#[derive(Component)]
+struct SlotLevel(Slot);
+
+#[derive(Component)]
+struct CooldownRadial;
+
+#[derive(Component)]
+struct LevelUpBanner(Timer);
+
+fn setup_hud(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<CooldownMaterial>>,
+) {
+    let font = asset_server.load(widgets::FONT);
+    let text = |size: f32| TextFont {
+        font: font.clone(),
+        font_size: size,
+        ..default()
+    };
+    let cooldown = materials.add(CooldownMaterial {
+        progress: Vec4::ZERO,
+        color: Color::srgba(0.0, 0.0, 0.0, 0.65).to_linear().to_vec4(),
+    });
+
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            padding: UiRect::all(Val::Px(12.0)),
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::SpaceBetween,
+            ..default()
+        },
+        Hud,
+    )).with_children(|root| {
+        // Top bar: health and experience on the left, timer in the middle, score on the right
+        root.spawn(Node {
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::SpaceBetween,
+            align_items: AlignItems::FlexStart,
+            ..default()
+        }).with_children(|top| {
+            top.spawn(Node {
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(6.0),
+                ..default()
+            }).with_children(|bars| {
+                bars.spawn((
+                    Node {
+                        width: Val::Px(320.0),
+                        height: Val::Px(26.0),
+                        border: UiRect::all(Val::Px(2.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BorderColor::all(Color::srgb(0.6, 0.6, 0.6)),
+                    BackgroundColor(Color::srgb(0.3, 0.3, 0.3)),
+                )).with_children(|bar| {
+                    bar.spawn((
+                        Node {
+                            position_type: PositionType::Absolute,
+                            left: Val::Px(0.0),
+                            width: Val::Percent(100.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgb(0.0, 0.8, 0.0)),
+                        HealthFill,
+                    ));
+                    bar.spawn((Text::new(""), text(16.0), TextColor(Color::WHITE), HealthLabel));
+                });
+
+                bars.spawn(Node {
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(8.0),
+                    ..default()
+                }).with_children(|row| {
+                    row.spawn((Localized::new("hud.level").with("level", 1), text(16.0), TextColor(Color::WHITE), LevelText));
+                    row.spawn((
+                        Node {
+                            width: Val::Px(266.0),
+                            height: Val::Px(10.0),
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgb(0.2, 0.2, 0.25)),
+                    )).with_children(|bar| {
+                        bar.spawn((
+                            Node {
+                                width: Val::Percent(0.0),
+                                height: Val::Percent(100.0),
+                                ..default()
+                            },
+                            BackgroundColor(Color::srgb(0.3, 0.6, 1.0)),
+                            ExperienceFill,
+                        ));
+                    });
+                });
+            });
+
+            top.spawn((Text::new("00:00"), text(32.0), TextColor(Color::WHITE), TimerText));
+
+            top.spawn(Node {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::FlexEnd,
+                ..default()
+            }).with_children(|right| {
+                right.spawn((Localized::new("hud.score").with("score", 0), text(30.0), TextColor(Color::WHITE), ScoreText));
+                right.spawn((Localized::new("hud.kills").with("kills", 0), text(18.0), TextColor(Color::srgb(0.8, 0.8, 0.8)), KillsText));
+            });
+        });
+
+        // Bottom bar: equipped weapon and passive
+        root.spawn(Node {
+            column_gap: Val::Px(8.0),
+            ..default()
+        }).with_children(|slots| {
+            for slot in [Slot::Weapon, Slot::Passive] {
+                slots.spawn((
+                    Node {
+                        width: Val::Px(60.0),
+                        height: Val::Px(60.0),
+                        border: UiRect::all(Val::Px(2.0)),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    BorderColor::all(Color::srgb(0.6, 0.6, 0.6)),
+                    BackgroundColor(Color::srgb(0.15, 0.15, 0.16)),
+                )).with_children(|parent| {
+                    parent.spawn((
+                        ImageNode::default(),
+                        Node {
+                            width: Val::Px(40.0),
+                            height: Val::Px(40.0),
+                            ..default()
+                        },
+                        SlotIcon(slot),
+                    ));
+                    if slot == Slot::Weapon {
+                        parent.spawn((
+                            Node {
+                                position_type: PositionType::Absolute,
+                                width: Val::Percent(100.0),
+                                height: Val::Percent(100.0),
+                                ..default()
+                            },
+                            MaterialNode(cooldown.clone()),
+                            CooldownRadial,
+                        ));
+                    }
+                    parent.spawn((
+                        Localized::new("hud.slot_level").with("level", 1),
+                        text(12.0),
+                        TextColor(Color::WHITE),
+                        Node {
+                            position_type: PositionType::Absolute,
+                            right: Val::Px(2.0),
+                            bottom: Val::Px(0.0),
+                            ..default()
+                        },
+                        SlotLevel(slot),
+                    ));
+                });
+            }
+        });
+
+        root.spawn((
+            Localized::new("hud.level_up").with("level", 2),
+            text(48.0),
+            TextColor(Color::srgb(1.0, 0.85, 0.2)),
+            TextLayout::new_with_justify(Justify::Center),
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Percent(25.0),
+                width: Val::Percent(100.0),
+                ..default()
+            },
+            Visibility::Hidden,
+            LevelUpBanner(Timer::from_seconds(1.5, TimerMode::Once)),
+        ));
+    });
+}
+
+fn update_health_bar(
+    accessibility: Res<AccessibilitySettings>,
+    player_query: Query<&Health, (With<Player>, Changed<Health>)>,
+    mut fill_query: Query<(&mut Node, &mut BackgroundColor), With<HealthFill>>,
+    mut label_query: Query<&mut Text, With<HealthLabel>>,
+) {
+    let Ok(health) = player_query.single() else {
+        return;
+    };
+    let percent = (health.current / health.max).clamp(0.0, 1.0) * 100.0;
+    for (mut node, mut color) in fill_query.iter_mut() {
+        node.width = Val::Percent(percent);
+        color.0 = accessibility.palette.colors().player_health;
+    }
+    for mut text in label_query.iter_mut() {
+        **text = format!("{:.0} / {:.0}", health.current.ceil(), health.max);
+    }
+}
+
+fn update_timer(
+    stats: Res<RunStats>,
+    mut shown: Local<Option<u32>>,
+    mut query: Query<&mut Text, With<TimerText>>,
+) {
+    let secs = stats.survival_time as u32;
+    if !stats.is_changed() || *shown == Some(secs) {
+        return;
+    }
+    *shown = Some(secs);
+    for mut text in query.iter_mut() {
+        **text = format!("{:02}:{:02}", secs / 60, secs % 60);
+    }
+}
+
+fn update_score_and_kills(
+    score: Res<Score>,
+    stats: Res<RunStats>,
+    mut shown_kills: Local<Option<u32>>,
+    mut score_query: Query<&mut Localized, (With<ScoreText>, Without<KillsText>)>,
+    mut kills_query: Query<&mut Localized, (With<KillsText>, Without<ScoreText>)>,
+) {
+    if score.is_changed() {
+        for mut text in score_query.iter_mut() {
+            *text = Localized::new("hud.score").with("score", score.0);
+        }
+    }
+    // RunStats changes every frame with the survival time, so compare against what's shown
+    if stats.is_changed() && *shown_kills != Some(stats.kills) {
+        *shown_kills = Some(stats.kills);
+        for mut text in kills_query.iter_mut() {
+            *text = Localized::new("hud.kills").with("kills", stats.kills);
+        }
+    }
+}
+
+fn update_experience(
+    experience: Res<Experience>,
+    mut fill_query: Query<&mut Node, With<ExperienceFill>>,
+    mut level_query: Query<&mut Localized, With<LevelText>>,
+) {
+    if !experience.is_changed() {
+        return;
+    }
+    let percent = experience.xp as f32 / experience.xp_to_next() as f32 * 100.0;
+    for mut node in fill_query.iter_mut() {
+        node.width = Val::Percent(percent.min(100.0));
+    }
+    for mut text in level_query.iter_mut() {
+        *text = Localized::new("hud.level").with("level", experience.level);
+    }
+}
+
+fn set_slot_icons(
+    tileset: Res<Tileset>,
+    weapon_query: Query<&Weapon, (With<Player>, Added<Weapon>)>,
+    passive_query: Query<&Passive, (With<Player>, Added<Passive>)>,
+    mut icon_query: Query<(&SlotIcon, &mut ImageNode)>,
+) {
+    for (icon, mut image) in icon_query.iter_mut() {
+        let tile = match icon.0 {
+            Slot::Weapon => weapon_query.single().ok().map(|weapon| weapon.icon.clone()),
+            Slot::Passive => passive_query.single().ok().map(|passive| passive.perk.icon()),
+        };
+        if let Some(tile) = tile {
+            *image = tileset.image_node(tileset.index(&tile));
+        }
+    }
+}
+
+fn update_weapon_slot(
+    weapon_query: Query<&Weapon, (With<Player>, Changed<Weapon>)>,
+    radial_query: Query<&MaterialNode<CooldownMaterial>, With<CooldownRadial>>,
+    mut materials: ResMut<Assets<CooldownMaterial>>,
+    mut level_query: Query<(&SlotLevel, &mut Localized)>,
+) {
+    let Ok(weapon) = weapon_query.single() else {
+        return;
+    };
+
+    let remaining = weapon.cooldown.fraction_remaining();
+    for handle in radial_query.iter() {
+        // Only touch the asset when the value moves, so the material isn't re-uploaded every frame
+        if materials.get(&handle.0).is_some_and(|material| material.progress.x != remaining) {
+            if let Some(material) = materials.get_mut(&handle.0) {
+                material.progress.x = remaining;
+            }
+        }
+    }
+
+    for (slot, mut text) in level_query.iter_mut() {
+        if slot.0 == Slot::Weapon {
+            text.set_if_neq(Localized::new("hud.slot_level").with("level", weapon.level));
+        }
+    }
+}
+
+fn update_passive_slot(
+    passive_query: Query<&Passive, (With<Player>, Changed<Passive>)>,
+    mut level_query: Query<(&SlotLevel, &mut Localized)>,
+) {
+    let Ok(passive) = passive_query.single() else {
+        return;
+    };
+    for (slot, mut text) in level_query.iter_mut() {
+        if slot.0 == Slot::Passive {
+            *text = Localized::new("hud.slot_level").with("level", passive.level);
+        }
+    }
+}
+
+fn show_level_up(
+    time: Res<Time>,
+    mut level_ups: MessageReader<LevelUp>,
+    mut banner_query: Query<(&mut Visibility, &mut Localized, &mut LevelUpBanner)>,
+) {
+    let Ok((mut visibility, mut text, mut banner)) = banner_query.single_mut() else {
+        return;
+    };
+
+    if let Some(level_up) = level_ups.read().last() {
+        *text = Localized::new("hud.level_up").with("level", level_up.level);
+        *visibility = Visibility::Inherited;
+        banner.0.reset();
+    }
+
+    if *visibility != Visibility::Hidden {
+        banner.0.tick(time.delta());
+        if banner.0.is_finished() {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
+
+fn cleanup_hud(mut commands: Commands, query: Query<Entity, With<Hud>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
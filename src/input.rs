@@ -0,0 +1,472 @@
+use std::collections::{BTreeMap, HashSet};
+
+use bevy::input::InputSystems;
+use bevy::input::mouse::AccumulatedMouseMotion;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::locale::{Localized, Strings};
+use crate::settings::Settings;
+use crate::widgets::{self, ButtonPressed, Focused, RowValue, WidgetSystems};
+use crate::GameState;
+
+// Actions Plugin
+pub struct ActionsPlugin;
+
+impl Plugin for ActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Bindings>()
+            .init_resource::<ActionState>()
+            .init_resource::<KeyboardCaptured>()
+            .add_systems(PreUpdate, update_action_state.after(InputSystems))
+            .add_systems(OnEnter(GameState::Controls), setup_controls)
+            .add_systems(
+                Update,
+                (controls_input, update_controls_text).chain().after(WidgetSystems).run_if(in_state(GameState::Controls)),
+            )
+            .add_systems(OnExit(GameState::Controls), cleanup_controls);
+    }
+}
+
+/// Most bindings one action can have; binding another drops the oldest.
+const MAX_BINDINGS: usize = 4;
+
+/// Inputs that back out of rebinding on the controls screen instead of being bound.
+const CANCEL_BINDINGS: [Binding; 2] = [Binding::Key(KeyCode::Escape), Binding::Gamepad(GamepadButton::East)];
+
+/// How far a stick has to be pushed to count as a menu step.
+const STICK_STEP_THRESHOLD: f32 = 0.6;
+
+/// Everything the player can do, independent of which keys or buttons do it.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Fire,
+    AutoAim,
+    Pause,
+    Confirm,
+    Back,
+}
+
+impl Action {
+    pub const ALL: [Action; 9] = [
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Fire,
+        Action::AutoAim,
+        Action::Pause,
+        Action::Confirm,
+        Action::Back,
+    ];
+
+    /// Key of the action's name in the string tables.
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::MoveUp => "action.move_up",
+            Action::MoveDown => "action.move_down",
+            Action::MoveLeft => "action.move_left",
+            Action::MoveRight => "action.move_right",
+            Action::Fire => "action.fire",
+            Action::AutoAim => "action.auto_aim",
+            Action::Pause => "action.pause",
+            Action::Confirm => "action.confirm",
+            Action::Back => "action.back",
+        }
+    }
+}
+
+/// A physical input that can trigger an action.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Gamepad(GamepadButton),
+}
+
+impl Binding {
+    /// Short name shown in hints and on the controls screen, e.g. `W` or `Mouse Left`.
+    pub fn label(self) -> String {
+        match self {
+            Binding::Key(key) => {
+                let name = format!("{key:?}");
+                let name = name.strip_prefix("Key").or_else(|| name.strip_prefix("Digit")).unwrap_or(&name).to_string();
+                name.to_uppercase()
+            }
+            Binding::Mouse(MouseButton::Other(button)) => format!("MOUSE {button}"),
+            Binding::Mouse(button) => format!("MOUSE {}", format!("{button:?}").to_uppercase()),
+            Binding::Gamepad(GamepadButton::Other(button)) => format!("PAD {button}"),
+            Binding::Gamepad(button) => format!("PAD {}", format!("{button:?}").to_uppercase()),
+        }
+    }
+}
+
+/// Which inputs trigger each action. Saved with the rest of the `Settings`, which keep the
+/// resource in sync.
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct Bindings {
+    pub actions: BTreeMap<Action, Vec<Binding>>,
+    /// Stick deflection below this is ignored, so worn sticks don't drift.
+    pub stick_dead_zone: f32,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        use Binding::{Gamepad, Key, Mouse};
+        Self {
+            actions: BTreeMap::from([
+                (Action::MoveUp, vec![Key(KeyCode::KeyW), Key(KeyCode::ArrowUp), Gamepad(GamepadButton::DPadUp)]),
+                (Action::MoveDown, vec![Key(KeyCode::KeyS), Key(KeyCode::ArrowDown), Gamepad(GamepadButton::DPadDown)]),
+                (Action::MoveLeft, vec![Key(KeyCode::KeyA), Key(KeyCode::ArrowLeft), Gamepad(GamepadButton::DPadLeft)]),
+                (Action::MoveRight, vec![Key(KeyCode::KeyD), Key(KeyCode::ArrowRight), Gamepad(GamepadButton::DPadRight)]),
+                (Action::Fire, vec![Mouse(MouseButton::Left), Gamepad(GamepadButton::RightTrigger2)]),
+                (Action::AutoAim, vec![Key(KeyCode::KeyT), Gamepad(GamepadButton::North)]),
+                (Action::Pause, vec![Key(KeyCode::Escape), Key(KeyCode::KeyP), Gamepad(GamepadButton::Start)]),
+                (Action::Confirm, vec![Key(KeyCode::Space), Key(KeyCode::Enter), Gamepad(GamepadButton::South)]),
+                (Action::Back, vec![Key(KeyCode::Escape), Key(KeyCode::Backspace), Gamepad(GamepadButton::East)]),
+            ]),
+            stick_dead_zone: 0.2,
+        }
+    }
+}
+
+impl Bindings {
+    /// Gives actions missing from a saved file their default bindings.
+    pub fn fill_missing(&mut self) {
+        for (action, defaults) in Bindings::default().actions {
+            let entry = self.actions.entry(action).or_default();
+            if entry.is_empty() {
+                *entry = defaults;
+            }
+        }
+    }
+
+    pub fn get(&self, action: Action) -> &[Binding] {
+        self.actions.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// All of an action's bindings joined for display, e.g. `W/ARROWUP`.
+    pub fn label(&self, action: Action) -> String {
+        self.get(action).iter().map(|binding| binding.label()).collect::<Vec<_>>().join("/")
+    }
+
+    /// Like `label`, leaving out inputs also bound to `other`, for screens where `other` wins
+    /// when both fire.
+    pub fn label_without(&self, action: Action, other: Action) -> String {
+        let shadowed = self.get(other);
+        self.get(action).iter().filter(|binding| !shadowed.contains(binding)).map(|binding| binding.label()).collect::<Vec<_>>().join("/")
+    }
+
+    /// Binds an input to an action, or unbinds it if it was already bound. An action always
+    /// keeps at least one binding.
+    fn toggle(&mut self, action: Action, binding: Binding) {
+        let bound = self.actions.entry(action).or_default();
+        if let Some(index) = bound.iter().position(|existing| *existing == binding) {
+            if bound.len() > 1 {
+                bound.remove(index);
+            }
+        } else {
+            bound.push(binding);
+            if bound.len() > MAX_BINDINGS {
+                bound.remove(0);
+            }
+        }
+    }
+}
+
+/// Which kind of device the player used last. Aiming follows it, so picking up a gamepad
+/// mid-run switches to stick aim and touching the mouse switches back.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+enum InputDevice {
+    #[default]
+    KeyboardMouse,
+    Gamepad(Entity),
+}
+
+/// Set while a text field or the controls screen has the keyboard, so typing into it doesn't
+/// trigger actions.
+#[derive(Resource, Default, PartialEq)]
+pub struct KeyboardCaptured(pub bool);
+
+/// The actions triggered this frame, read by gameplay and menus instead of raw input.
+#[derive(Resource, Default)]
+pub struct ActionState {
+    pressed: HashSet<Action>,
+    just_pressed: HashSet<Action>,
+    /// Cursor position in world space, if it is over the window.
+    cursor: Option<Vec2>,
+    /// Left and right stick of the active gamepad, with the dead-zone taken out.
+    move_stick: Vec2,
+    aim_stick: Vec2,
+    /// Direction the left stick was pushed past the step threshold last frame, so holding it
+    /// steps through a menu once.
+    stick_step: IVec2,
+    device: InputDevice,
+}
+
+impl ActionState {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    /// Movement from the four move actions, normalized so diagonals aren't faster. The left
+    /// stick is analog, so a light push walks slower.
+    pub fn move_axis(&self) -> Vec2 {
+        let axis = |negative, positive| (self.pressed(positive) as i32 - self.pressed(negative) as i32) as f32;
+        let digital = Vec2::new(axis(Action::MoveLeft, Action::MoveRight), axis(Action::MoveDown, Action::MoveUp)).normalize_or_zero();
+        (digital + self.move_stick).clamp_length_max(1.0)
+    }
+
+    /// Direction to aim in from `origin`: towards the cursor, or along the right stick when a
+    /// gamepad is in use. `None` when there is nothing to aim with, e.g. the stick is at rest.
+    pub fn aim(&self, origin: Vec2) -> Option<Vec2> {
+        let aim = match self.device {
+            InputDevice::KeyboardMouse => self.cursor.map(|cursor| (cursor - origin).normalize_or_zero()),
+            InputDevice::Gamepad(_) => Some(self.aim_stick.normalize_or_zero()),
+        };
+        aim.filter(|aim| *aim != Vec2::ZERO)
+    }
+}
+
+/// Rescales a stick so the dead-zone edge reads as 0 and full deflection as 1.
+fn apply_dead_zone(stick: Vec2, dead_zone: f32) -> Vec2 {
+    let length = stick.length();
+    if length <= dead_zone {
+        return Vec2::ZERO;
+    }
+    stick / length * ((length - dead_zone) / (1.0 - dead_zone).max(f32::EPSILON)).min(1.0)
+}
+
+fn update_action_state(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mouse_motion: Res<AccumulatedMouseMotion>,
+    bindings: Res<Bindings>,
+    captured: Res<KeyboardCaptured>,
+    mut state: ResMut<ActionState>,
+    windows: Query<&Window>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    gamepads: Query<(Entity, &Gamepad)>,
+) {
+    let state = &mut *state;
+    state.pressed.clear();
+    state.just_pressed.clear();
+
+    // Switch to whichever device was touched last, and back to keyboard and mouse if the
+    // gamepad in use is unplugged
+    let dead_zone = bindings.stick_dead_zone;
+    let touched = gamepads.iter().find(|(_, gamepad)| {
+        gamepad.get_just_pressed().next().is_some()
+            || gamepad.left_stick().length() > dead_zone
+            || gamepad.right_stick().length() > dead_zone
+    });
+    if let Some((entity, _)) = touched {
+        state.device = InputDevice::Gamepad(entity);
+    } else if keys.get_just_pressed().next().is_some() || mouse.get_just_pressed().next().is_some() || mouse_motion.delta != Vec2::ZERO {
+        state.device = InputDevice::KeyboardMouse;
+    }
+    let gamepad = match state.device {
+        InputDevice::Gamepad(entity) => gamepads.get(entity).ok().map(|(_, gamepad)| gamepad),
+        InputDevice::KeyboardMouse => None,
+    };
+    if gamepad.is_none() {
+        state.device = InputDevice::KeyboardMouse;
+    }
+
+    for (action, bound) in &bindings.actions {
+        for binding in bound {
+            let (pressed, just_pressed) = match *binding {
+                Binding::Key(_) if captured.0 => (false, false),
+                Binding::Key(key) => (keys.pressed(key), keys.just_pressed(key)),
+                Binding::Mouse(button) => (mouse.pressed(button), mouse.just_pressed(button)),
+                Binding::Gamepad(button) => gamepad.map_or((false, false), |gamepad| (gamepad.pressed(button), gamepad.just_pressed(button))),
+            };
+            if pressed {
+                state.pressed.insert(*action);
+            }
+            if just_pressed {
+                state.just_pressed.insert(*action);
+            }
+        }
+    }
+
+    state.move_stick = gamepad.map_or(Vec2::ZERO, |gamepad| apply_dead_zone(gamepad.left_stick(), dead_zone));
+    state.aim_stick = gamepad.map_or(Vec2::ZERO, |gamepad| apply_dead_zone(gamepad.right_stick(), dead_zone));
+
+    // Pushing the left stick steps through menus like the d-pad
+    let step = |value: f32| if value > STICK_STEP_THRESHOLD { 1 } else if value < -STICK_STEP_THRESHOLD { -1 } else { 0 };
+    let stick = gamepad.map_or(Vec2::ZERO, Gamepad::left_stick);
+    let stick_step = IVec2::new(step(stick.x), step(stick.y));
+    for (value, last, negative, positive) in [
+        (stick_step.x, state.stick_step.x, Action::MoveLeft, Action::MoveRight),
+        (stick_step.y, state.stick_step.y, Action::MoveDown, Action::MoveUp),
+    ] {
+        if value != 0 && value != last {
+            state.just_pressed.insert(if value > 0 { positive } else { negative });
+        }
+    }
+    state.stick_step = stick_step;
+
+    state.cursor = match (windows.single(), camera_query.single()) {
+        (Ok(window), Ok((camera, camera_transform))) => {
+            window.cursor_position().and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor).ok())
+        }
+        _ => None,
+    };
+}
+
+#[derive(Component)]
+struct ControlsUI;
+
+#[derive(Component, Clone, Copy)]
+enum ControlsButton {
+    Action(Action),
+    Reset,
+    Done,
+}
+
+#[derive(Resource, Default)]
+struct ControlsMenu {
+    /// Button waiting for an input to bind to its action.
+    listening: Option<Entity>,
+}
+
+fn setup_controls(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.init_resource::<ControlsMenu>();
+    let font = asset_server.load(widgets::FONT);
+    commands.spawn((widgets::screen(), ControlsUI)).with_children(|parent| {
+        parent.spawn(widgets::title(&font, "controls.title"));
+        parent
+            .spawn(Node {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(4.0),
+                ..default()
+            })
+            .with_children(|column| {
+                for action in Action::ALL {
+                    column.spawn((widgets::row(&font, action.name()), ControlsButton::Action(action)));
+                }
+                column.spawn((widgets::button(&font, "common.reset"), ControlsButton::Reset));
+                column.spawn((widgets::button(&font, "common.done"), ControlsButton::Done));
+            });
+        let cancel = CANCEL_BINDINGS.map(Binding::label).join("/");
+        parent.spawn(widgets::hint(&font, Localized::new("controls.hint").with("cancel", cancel)));
+    });
+}
+
+fn controls_input(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    gamepads: Query<&Gamepad>,
+    actions: Res<ActionState>,
+    mut pressed: MessageReader<ButtonPressed>,
+    button_query: Query<&ControlsButton>,
+    focused_query: Query<Entity, With<Focused>>,
+    mut settings: ResMut<Settings>,
+    mut menu: ResMut<ControlsMenu>,
+    mut captured: ResMut<KeyboardCaptured>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if let Some(listening) = menu.listening {
+        // The input being bound must not also press or move between buttons
+        pressed.clear();
+        let binding = keys
+            .get_just_pressed()
+            .next()
+            .map(|key| Binding::Key(*key))
+            .or_else(|| mouse.get_just_pressed().next().map(|button| Binding::Mouse(*button)))
+            .or_else(|| gamepads.iter().find_map(|gamepad| gamepad.get_just_pressed().next().map(|button| Binding::Gamepad(*button))));
+        if let Some(binding) = binding {
+            if let (Ok(ControlsButton::Action(action)), false) = (button_query.get(listening), CANCEL_BINDINGS.contains(&binding)) {
+                settings.bindings.toggle(*action, binding);
+            }
+            for entity in focused_query.iter() {
+                commands.entity(entity).remove::<Focused>();
+            }
+            commands.entity(listening).insert(Focused);
+            menu.listening = None;
+            captured.0 = false;
+        }
+        return;
+    }
+
+    if actions.just_pressed(Action::Back) {
+        next_state.set(GameState::Settings);
+        return;
+    }
+    for ButtonPressed(entity) in pressed.read() {
+        match button_query.get(*entity) {
+            Ok(ControlsButton::Action(_)) => {
+                menu.listening = Some(*entity);
+                captured.0 = true;
+            }
+            Ok(ControlsButton::Reset) => settings.bindings = Bindings::default(),
+            Ok(ControlsButton::Done) => next_state.set(GameState::Settings),
+            Err(_) => {}
+        }
+    }
+}
+
+fn update_controls_text(
+    settings: Res<Settings>,
+    menu: Res<ControlsMenu>,
+    strings: Strings,
+    button_query: Query<(Entity, Ref<ControlsButton>, &Children)>,
+    mut text_query: Query<&mut Text, With<RowValue>>,
+) {
+    for (entity, button, children) in button_query.iter() {
+        if !settings.is_changed() && !menu.is_changed() && !strings.is_changed() && !button.is_added() {
+            continue;
+        }
+        let ControlsButton::Action(action) = *button else {
+            continue;
+        };
+        let bound = if menu.listening == Some(entity) { strings.get("controls.listening") } else { settings.bindings.label(action) };
+        let mut texts = text_query.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.0.clone_from(&bound);
+        }
+    }
+}
+
+/// Saves any changed bindings on the way out.
+fn cleanup_controls(mut commands: Commands, settings: Res<Settings>, mut captured: ResMut<KeyboardCaptured>, query: Query<Entity, With<ControlsUI>>) {
+    settings.save();
+    captured.0 = false;
+    commands.remove_resource::<ControlsMenu>();
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toggling_unbinds_all_but_the_last_binding() {
+        let mut bindings = Bindings::default();
+        bindings.toggle(Action::Back, Binding::Key(KeyCode::Escape));
+        bindings.toggle(Action::Back, Binding::Key(KeyCode::Backspace));
+        assert_eq!(bindings.get(Action::Back), [Binding::Gamepad(GamepadButton::East)]);
+        bindings.toggle(Action::Back, Binding::Gamepad(GamepadButton::East));
+        assert_eq!(bindings.get(Action::Back), [Binding::Gamepad(GamepadButton::East)]);
+    }
+
+    #[test]
+    fn labels_leave_out_inputs_another_action_wins() {
+        let bindings = Bindings::default();
+        assert_eq!(bindings.label_without(Action::Pause, Action::Back), "P/PAD START");
+    }
+}
@@ -0,0 +1,291 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::ui::UiSystems;
+use ron::extensions::Extensions;
+use serde::Deserialize;
+
+use crate::data::RonAssetLoader;
+
+// Locale Plugin
+pub struct LocalePlugin;
+
+impl Plugin for LocalePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<StringTable>()
+            .register_asset_loader(RonAssetLoader::<StringTable>::default())
+            .init_resource::<Locale>()
+            .add_systems(Startup, load_string_tables)
+            .add_systems(Update, refresh_on_load)
+            .add_systems(PostUpdate, localize_texts.before(UiSystems::Prepare));
+    }
+}
+
+/// Codes of the shipped languages, each with a table at `assets/locales/<code>.ron`. The first
+/// is the default and fills in strings missing from the others.
+pub const LANGUAGES: [&str; 2] = ["en", "es"];
+
+/// Every UI string in one language, loaded from `assets/locales/<code>.ron`.
+#[derive(Asset, TypePath, Deserialize, Clone, Debug)]
+pub struct StringTable {
+    /// The language's name for itself, shown in the settings.
+    pub name: String,
+    #[serde(default)]
+    pub plurals: PluralRule,
+    pub strings: HashMap<String, Translation>,
+}
+
+impl StringTable {
+    fn parse(source: &str) -> ron::error::SpannedResult<Self> {
+        ron::Options::default().with_default_extension(Extensions::IMPLICIT_SOME).from_str(source)
+    }
+
+    /// The text for `key`, in the plural form for `count` if it has plural forms.
+    fn text(&self, key: &str, count: Option<u32>) -> Option<&str> {
+        Some(match self.strings.get(key)? {
+            Translation::Text(text) => text,
+            Translation::Plural(forms) => forms.select(self.plurals.category(count.unwrap_or(0))),
+        })
+    }
+}
+
+/// A string, or one string per plural form for strings that show a count.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(untagged)]
+pub enum Translation {
+    Text(String),
+    Plural(PluralForms),
+}
+
+/// The forms a language's plural rule picks from. Forms a language doesn't use can be left out;
+/// `other` is used for any form that is missing.
+#[derive(Deserialize, Clone, Debug)]
+pub struct PluralForms {
+    pub one: Option<String>,
+    pub other: String,
+}
+
+impl PluralForms {
+    fn select(&self, category: PluralCategory) -> &str {
+        let form = match category {
+            PluralCategory::One => &self.one,
+            PluralCategory::Other => &None,
+        };
+        form.as_deref().unwrap_or(&self.other)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum PluralCategory {
+    One,
+    Other,
+}
+
+/// How a language picks the plural form for a count, after the CLDR plural rules.
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum PluralRule {
+    /// `one` for 1 and `other` for the rest, as in English, Spanish and German.
+    #[default]
+    OneOther,
+}
+
+impl PluralRule {
+    fn category(self, count: u32) -> PluralCategory {
+        match self {
+            PluralRule::OneOther if count == 1 => PluralCategory::One,
+            PluralRule::OneOther => PluralCategory::Other,
+        }
+    }
+}
+
+/// The language UI text is shown in.
+#[derive(Resource)]
+pub struct Locale {
+    /// Code of the current language, one of `LANGUAGES`. Set from the settings.
+    pub language: String,
+    /// Table for each of `LANGUAGES`, in the same order.
+    tables: Vec<Handle<StringTable>>,
+    /// Built-in copy of the default language, so text is readable before the tables load.
+    fallback: StringTable,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self {
+            language: LANGUAGES[0].to_string(),
+            tables: Vec::new(),
+            fallback: StringTable::parse(include_str!("../assets/locales/en.ron")).expect("built-in string table is valid"),
+        }
+    }
+}
+
+fn load_string_tables(mut locale: ResMut<Locale>, asset_server: Res<AssetServer>) {
+    locale.tables = LANGUAGES.iter().map(|code| asset_server.load(format!("locales/{code}.ron"))).collect();
+}
+
+/// Marks the locale changed when a table finishes loading or is edited, so text showing it
+/// is redrawn.
+fn refresh_on_load(mut events: MessageReader<AssetEvent<StringTable>>, mut locale: ResMut<Locale>) {
+    if events.read().any(|event| matches!(event, AssetEvent::LoadedWithDependencies { .. } | AssetEvent::Modified { .. })) {
+        locale.set_changed();
+    }
+}
+
+/// Looks up UI strings in the current language. A string missing from it comes from the
+/// default language, and failing that shows its key.
+#[derive(SystemParam)]
+pub struct Strings<'w> {
+    locale: Res<'w, Locale>,
+    tables: Res<'w, Assets<StringTable>>,
+}
+
+impl Strings<'_> {
+    fn table(&self, code: &str) -> Option<&StringTable> {
+        let index = LANGUAGES.iter().position(|language| *language == code)?;
+        self.tables.get(self.locale.tables.get(index)?)
+    }
+
+    fn text<'a>(&'a self, key: &'a str, count: Option<u32>) -> &'a str {
+        self.table(&self.locale.language)
+            .and_then(|table| table.text(key, count))
+            .or_else(|| self.table(LANGUAGES[0]).and_then(|table| table.text(key, count)))
+            .or_else(|| self.locale.fallback.text(key, count))
+            .unwrap_or(key)
+    }
+
+    pub fn get(&self, key: &str) -> String {
+        self.text(key, None).to_string()
+    }
+
+    /// The string for `key` with each `{name}` in it replaced by the argument of that name.
+    pub fn format(&self, key: &str, args: &[(&str, &dyn Display)]) -> String {
+        substitute(self.text(key, None), args)
+    }
+
+    /// Like [`Strings::format`], in the plural form for `count`, which is also the `{count}`
+    /// argument.
+    pub fn plural(&self, key: &str, count: u32, args: &[(&str, &dyn Display)]) -> String {
+        let mut args = args.to_vec();
+        args.push(("count", &count));
+        substitute(self.text(key, Some(count)), &args)
+    }
+
+    /// The name a language calls itself, or its code while its table is loading.
+    pub fn language_name(&self, code: &str) -> String {
+        self.table(code).map_or_else(|| code.to_string(), |table| table.name.clone())
+    }
+
+    /// Whether the language changed or a table (re)loaded since the system last ran.
+    pub fn is_changed(&self) -> bool {
+        self.locale.is_changed()
+    }
+}
+
+fn substitute(template: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut text = template.to_string();
+    for (name, value) in args {
+        text = text.replace(&format!("{{{name}}}"), &value.to_string());
+    }
+    text
+}
+
+/// Text showing the string for a key, redrawn when the language changes.
+#[derive(Component, Clone, PartialEq, Debug)]
+#[require(Text)]
+pub struct Localized {
+    pub key: &'static str,
+    /// Values for the string's `{name}` placeholders.
+    pub args: Vec<(&'static str, String)>,
+}
+
+impl Localized {
+    pub fn new(key: &'static str) -> Self {
+        Self { key, args: Vec::new() }
+    }
+
+    pub fn with(mut self, name: &'static str, value: impl Display) -> Self {
+        self.args.push((name, value.to_string()));
+        self
+    }
+}
+
+impl From<&'static str> for Localized {
+    fn from(key: &'static str) -> Self {
+        Self::new(key)
+    }
+}
+
+fn localize_texts(strings: Strings, mut text_query: Query<(Ref<Localized>, &mut Text)>) {
+    let all = strings.is_changed();
+    for (localized, mut text) in text_query.iter_mut() {
+        if all || localized.is_changed() {
+            let args: Vec<(&str, &dyn Display)> = localized.args.iter().map(|(name, value)| (*name, value as &dyn Display)).collect();
+            **text = strings.format(localized.key, &args);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    const SHIPPED: [(&str, &str); 2] = [("en", include_str!("../assets/locales/en.ron")), ("es", include_str!("../assets/locales/es.ron"))];
+
+    /// The `{name}` placeholders in a string.
+    fn placeholders(text: &str) -> HashSet<&str> {
+        text.split('{').skip(1).filter_map(|part| part.split_once('}')).map(|(name, _)| name).collect()
+    }
+
+    fn forms(translation: &Translation) -> Vec<&str> {
+        match translation {
+            Translation::Text(text) => vec![text],
+            Translation::Plural(forms) => forms.one.iter().map(String::as_str).chain([forms.other.as_str()]).collect(),
+        }
+    }
+
+    #[test]
+    fn every_key_exists_in_every_shipped_locale() {
+        assert_eq!(SHIPPED.map(|(code, _)| code), LANGUAGES, "every language needs a table checked here");
+        let tables: Vec<(&str, StringTable)> = SHIPPED.iter().map(|(code, source)| (*code, StringTable::parse(source).unwrap_or_else(|error| panic!("{code}: {error}")))).collect();
+        let (_, english) = &tables[0];
+
+        for (code, table) in &tables {
+            for key in english.strings.keys() {
+                assert!(table.strings.contains_key(key), "{code} is missing {key}");
+            }
+            for (key, translation) in &table.strings {
+                let Some(original) = english.strings.get(key) else {
+                    panic!("{code} has {key}, which the game doesn't use");
+                };
+                assert_eq!(
+                    matches!(translation, Translation::Plural(_)),
+                    matches!(original, Translation::Plural(_)),
+                    "{code} {key} needs plural forms exactly where {} has them",
+                    LANGUAGES[0]
+                );
+                let expected = placeholders(forms(original).last().unwrap());
+                for form in forms(translation) {
+                    assert_eq!(placeholders(form), expected, "{code} {key} has different placeholders: {form}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn plural_rules_pick_forms_by_count() {
+        let categories = |rule: PluralRule| [0, 1, 2, 5, 11, 21, 22, 112].map(|count| rule.category(count));
+        use PluralCategory::*;
+        assert_eq!(categories(PluralRule::OneOther), [Other, One, Other, Other, Other, Other, Other, Other]);
+    }
+
+    #[test]
+    fn missing_plural_forms_use_other() {
+        let forms = PluralForms { one: None, other: "{count} runs".into() };
+        assert_eq!(forms.select(PluralCategory::One), "{count} runs");
+        assert_eq!(substitute(forms.select(PluralCategory::Other), &[("count", &3)]), "3 runs");
+    }
+}
@@ -1,11 +1,73 @@
 use bevy::prelude::*;
 use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+mod accessibility;
+mod animation;
+mod audio;
+mod camera;
+mod character;
+mod data;
+#[cfg(feature = "dev")]
+mod dev;
+mod enemy;
+mod feedback;
+mod flowfield;
+mod highscore;
+mod hud;
+mod input;
+mod locale;
+mod particles;
+mod physics;
+mod postprocess;
+mod profile;
+mod progression;
+mod save;
+mod settings;
+mod spiral;
+mod status;
+mod storage;
+mod tileset;
+mod widgets;
+mod world;
+
+use accessibility::{AccessibilityPlugin, AccessibilitySettings, OutlineSettings};
+use animation::{AnimationEvent, AnimationPlugin, Animator, ClipKind};
+use audio::{PlaySound, SoundPlugin};
+use camera::{CameraController, CameraControllerPlugin};
+use character::{Character, CharacterPlugin, CRIT_MULTIPLIER, Passive, SelectedCharacter, Weapon};
+use enemy::{EnemyArchetypes, EnemyPlugin, spawn_enemy};
+use feedback::FeedbackPlugin;
+use flowfield::{FlowField, FlowFieldPlugin};
+use highscore::HighScorePlugin;
+use hud::HudPlugin;
+use input::{Action, ActionState, ActionsPlugin, Bindings};
+use locale::{LocalePlugin, Localized};
+use particles::{EmitParticles, ParticlePlugin};
+use physics::{Explosion, Frozen, Impulse, OnHit, PhysicsPlugin, Stunned, Velocity};
+use postprocess::{PostProcessPlugin, ScreenEffects};
+use profile::{Profile, ProfilePlugin};
+use progression::{Experience, ProgressionPlugin};
+use save::{RunRng, SavePlugin, SavedRun};
+use settings::{Settings, SettingsPlugin};
+use spiral::{SpiralPlugin, SpiralSettings};
+use status::{StatusEffects, StatusKind, StatusPlugin};
+use tileset::{Tileset, TilesetPlugin};
+use widgets::{ButtonPressed, Disabled, WidgetsPlugin};
+use world::{WalkabilityGrid, WorldPlugin, offscreen_spawn_point, visible_area};
 
 // Game States
 #[derive(States, Debug, Clone, PartialEq, Eq, Hash, Default)]
 enum GameState {
+    /// Packing the tileset before anything is shown.
     #[default]
+    Loading,
     Menu,
+    CharacterSelect,
+    Settings,
+    /// Rebinding the controls, reached from the settings screen.
+    Controls,
+    HighScores,
     Playing,
     GameOver,
 }
@@ -13,6 +75,72 @@ enum GameState {
 #[derive(Component)]
 struct MenuUI;
 
+/// A button on the main menu.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+enum MenuEntry {
+    /// Only shown while there is a saved run to pick up.
+    Continue,
+    Start,
+    Characters,
+    Upgrades,
+    Settings,
+    HighScores,
+    Quit,
+}
+
+impl MenuEntry {
+    const ALL: [MenuEntry; 7] = [
+        MenuEntry::Continue,
+        MenuEntry::Start,
+        MenuEntry::Characters,
+        MenuEntry::Upgrades,
+        MenuEntry::Settings,
+        MenuEntry::HighScores,
+        MenuEntry::Quit,
+    ];
+
+    /// Key of the entry's label in the string tables.
+    fn label(self) -> &'static str {
+        match self {
+            MenuEntry::Continue => "menu.continue",
+            MenuEntry::Start => "menu.start",
+            MenuEntry::Characters => "menu.characters",
+            MenuEntry::Upgrades => "menu.upgrades",
+            MenuEntry::Settings => "menu.settings",
+            MenuEntry::HighScores => "menu.high_scores",
+            MenuEntry::Quit => "menu.quit",
+        }
+    }
+}
+
+/// Whether the run is paused. Virtual time stands still while it is.
+#[derive(Resource, Default)]
+struct Paused(bool);
+
+#[derive(Component)]
+struct PauseUI;
+
+#[derive(Component, Clone, Copy)]
+enum PauseButton {
+    Resume,
+    Quit,
+}
+
+/// Aiming help for players who can't aim with precision.
+#[derive(Resource)]
+struct AimSettings {
+    /// Shots go towards the nearest enemy in range instead of the cursor or stick.
+    auto_aim: bool,
+    /// How far away an enemy can be and still be targeted.
+    auto_aim_range: f32,
+}
+
+impl Default for AimSettings {
+    fn default() -> Self {
+        Self { auto_aim: false, auto_aim_range: 500.0 }
+    }
+}
+
 // Components
 #[derive(Component)]
 struct Player;
@@ -31,6 +159,8 @@ struct EnemySpeed(f32);
 #[derive(Component)]
 struct EnemyBullet;
 
+const ENEMY_BULLET_COLOR: Color = Color::srgb(0.8, 0.0, 0.8);
+
 #[derive(Component)]
 struct Speed(f32);
 
@@ -40,10 +170,12 @@ struct Damage(f32);
 #[derive(Component)]
 struct LastDirection(Vec3);
 
-#[derive(Component)]
+#[derive(Component, Serialize, Deserialize, Clone, PartialEq, Debug)]
 struct Bullet {
     velocity: Vec3,
     damage: f32,
+    crit: bool,
+    on_hit: OnHit,
 }
 
 // Health Plugin
@@ -52,11 +184,56 @@ struct HealthPlugin;
 impl Plugin for HealthPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(CollisionCooldown(Timer::from_seconds(0.5, TimerMode::Repeating)))
-            .add_systems(Update, (update_health_bars, check_collisions, check_death));
+            .add_message::<DamageEvent>()
+            .add_message::<DamageDealt>()
+            .configure_sets(Update, (DamageSystems::Deal, DamageSystems::Apply, DamageSystems::React).chain())
+            .add_systems(
+                Update,
+                (update_health_bars, check_collisions.in_set(DamageSystems::Deal), (apply_damage, check_death).chain().in_set(DamageSystems::Apply)),
+            );
     }
 }
 
-#[derive(Component)]
+/// Request to damage an entity with `Health`. Every source of damage goes through this.
+#[derive(Message)]
+struct DamageEvent {
+    target: Entity,
+    amount: f32,
+    crit: bool,
+    /// Where the damage came from, used to push the target away.
+    source: Vec3,
+    on_hit: OnHit,
+    /// Set when the damage is a tick of a status effect.
+    status: Option<StatusKind>,
+}
+
+/// Damage that was actually applied, after armor, for feedback systems to react to.
+#[derive(Message)]
+struct DamageDealt {
+    target: Entity,
+    amount: f32,
+    crit: bool,
+    position: Vec3,
+    /// Unit vector pointing away from the damage source.
+    direction: Vec2,
+    to_player: bool,
+    /// Whether this hit brought the target's health to zero.
+    killed: bool,
+    on_hit: OnHit,
+    status: Option<StatusKind>,
+}
+
+/// The order damage goes through a frame: sources send `DamageEvent`s, they are applied, and
+/// then whatever reacts to `DamageDealt` runs. Every message is read in the frame it is sent,
+/// so a run saved between frames has no hit left in flight.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+enum DamageSystems {
+    Deal,
+    Apply,
+    React,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone, PartialEq, Debug)]
 struct Health {
     current: f32,
     max: f32,
@@ -65,6 +242,19 @@ struct Health {
 #[derive(Component)]
 struct HealthBar;
 
+/// Impulse applied to both the player and the enemy when they touch.
+const CONTACT_KNOCKBACK: f32 = 300.0;
+
+/// Enemies closer than this to the player touch them. Approximate sprite size * scale.
+const CONTACT_RADIUS: f32 = 40.0;
+
+/// Bullets closer than this to a target hit it.
+const BULLET_HIT_RADIUS: f32 = 30.0;
+
+/// Bullets further than this from the player are despawned. The world is streamed in around the
+/// player without edges, so past the loaded chunks there would be no obstacles to stop them.
+const BULLET_RANGE: f32 = world::LOADED_RANGE;
+
 #[derive(Resource)]
 struct CollisionCooldown(Timer);
 
@@ -77,18 +267,18 @@ struct GameOverText;
 #[derive(Resource, Default)]
 struct Score(u32);
 
-#[derive(Component)]
-struct ScoreText;
+/// Bookkeeping for the current run, recorded in the high-score table when it ends.
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Default, Debug)]
+struct RunStats {
+    seed: u64,
+    survival_time: f32,
+    kills: u32,
+    character: String,
+}
 
 #[derive(Component)]
 struct Dead;
 
-#[derive(Component)]
-struct Particle {
-    velocity: Vec3,
-    lifetime: Timer,
-}
-
 #[derive(Component)]
 struct MeltParticle {
     lifetime: Timer,
@@ -111,53 +301,135 @@ impl Default for DeathTransition {
 }
 
 fn main() {
-    App::new()
-        .add_plugins(DefaultPlugins.set(ImagePlugin::default_nearest()))
+    let settings = Settings::load();
+    let mut app = App::new();
+    app
+        .add_plugins(
+            DefaultPlugins
+                .set(settings.image_plugin())
+                .set(WindowPlugin { primary_window: Some(settings.window()), ..default() }),
+        )
+        .insert_resource(settings)
+        .add_plugins(ActionsPlugin)
+        .add_plugins(LocalePlugin)
         .add_plugins(HealthPlugin)
+        .add_plugins(HighScorePlugin)
+        .add_plugins(ProfilePlugin)
+        .add_plugins(CharacterPlugin)
+        .add_plugins(ProgressionPlugin)
+        .add_plugins(HudPlugin)
+        .add_plugins(FeedbackPlugin)
+        .add_plugins(PhysicsPlugin)
+        .add_plugins(EnemyPlugin)
+        .add_plugins(StatusPlugin)
+        .add_plugins(WorldPlugin)
+        .add_plugins(TilesetPlugin)
+        .add_plugins(AnimationPlugin)
+        .add_plugins(FlowFieldPlugin)
+        .add_plugins(ParticlePlugin)
+        .add_plugins(CameraControllerPlugin)
+        .add_plugins(AccessibilityPlugin)
+        .add_plugins(PostProcessPlugin)
+        .add_plugins(SpiralPlugin)
+        .add_plugins(SoundPlugin)
+        .add_plugins(SettingsPlugin)
+        .add_plugins(WidgetsPlugin)
+        .add_plugins(SavePlugin)
         .init_state::<GameState>()
-        .insert_resource(ClearColor(Color::srgba(31.0/255.0, 32.0/255.0, 32.0/255.0, 1.0)))
+        .insert_resource(ClearColor(Color::srgba(34.0/255.0, 35.0/255.0, 35.0/255.0, 1.0))) // Background of the Colored tiles
         .insert_resource(EnemySpawnTimer(Timer::from_seconds(1.0, TimerMode::Repeating)))
         .init_resource::<Score>()
+        .init_resource::<RunStats>()
         .init_resource::<DeathTransition>()
+        .init_resource::<Paused>()
+        .init_resource::<AimSettings>()
         .add_systems(Startup, setup_camera)
-        .add_systems(OnEnter(GameState::Menu), setup_menu)
+        .add_systems(OnEnter(GameState::Menu), (cleanup_game, setup_menu))
         .add_systems(Update, menu_input.run_if(in_state(GameState::Menu)))
         .add_systems(OnExit(GameState::Menu), cleanup_menu)
-        .add_systems(OnEnter(GameState::Playing), setup_game)
-        .add_systems(Update, (move_player, move_enemies, camera_follow, shoot_bullet, enemy_shoot_bullets, move_bullets, check_bullet_collisions, update_score_text, update_particles, spawn_enemies).run_if(in_state(GameState::Playing)))
-        .add_systems(Update, update_death_transition.run_if(in_state(GameState::Playing)))
-        .run();
+        .add_systems(OnEnter(GameState::Playing), ((reset_run, setup_game).chain().run_if(not(save::resuming)), save::take_saved_run).chain())
+        .add_systems(
+            Update,
+            (move_player, move_enemies, shoot_bullet, enemy_shoot_bullets, move_bullets, check_bullet_collisions.in_set(DamageSystems::Deal), spawn_enemies, track_survival_time)
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(Update, (update_death_transition, pause_input, toggle_auto_aim).run_if(in_state(GameState::Playing)))
+        .add_systems(OnExit(GameState::Playing), end_pause);
+
+    #[cfg(feature = "dev")]
+    app.add_plugins(dev::DevPlugin);
+
+    app.run();
 }
 
 fn setup_camera(mut commands: Commands) {
-    commands.spawn(Camera2d);
+    // The spiral stays inactive until the player dies, and the outline until high contrast is on
+    commands.spawn((Camera2d, CameraController::default(), OutlineSettings::default(), ScreenEffects::default(), SpiralSettings::default()));
 }
 
-fn setup_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands.spawn((
-        Text::new("GRAGUSI SURVIVORS\n\nPress SPACE to Start\n\nWASD - Move\nLeft Click - Shoot"),
-        TextFont {
-            font: asset_server.load("font/BigBlueTerm437NerdFontMono-Regular.ttf"),
-            font_size: 32.0,
-            ..default()
-        },
-        TextColor(Color::WHITE),
-        Node {
-            position_type: PositionType::Absolute,
-            top: Val::Percent(30.0),
-            left: Val::Percent(50.0),
-            ..default()
-        },
-        MenuUI,
-    ));
+fn setup_menu(mut commands: Commands, asset_server: Res<AssetServer>, bindings: Res<Bindings>, saved: Res<SavedRun>) {
+    let font = asset_server.load(widgets::FONT);
+    commands.spawn((widgets::screen(), MenuUI)).with_children(|parent| {
+        parent.spawn(widgets::title(&font, "menu.title"));
+        parent
+            .spawn(Node {
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(8.0),
+                ..default()
+            })
+            .with_children(|column| {
+                for entry in MenuEntry::ALL {
+                    if entry == MenuEntry::Continue && saved.run.is_none() {
+                        continue;
+                    }
+                    let mut button = column.spawn((widgets::button(&font, entry.label()), entry));
+                    // There is nothing to upgrade between runs yet
+                    if entry == MenuEntry::Upgrades {
+                        button.insert(Disabled);
+                    }
+                }
+            });
+        parent.spawn(widgets::hint(
+            &font,
+            Localized::new("menu.hint")
+                .with("confirm", bindings.label(Action::Confirm))
+                .with("up", bindings.label(Action::MoveUp))
+                .with("left", bindings.label(Action::MoveLeft))
+                .with("down", bindings.label(Action::MoveDown))
+                .with("right", bindings.label(Action::MoveRight))
+                .with("fire", bindings.label(Action::Fire)),
+        ));
+    });
 }
 
 fn menu_input(
-    keys: Res<ButtonInput<KeyCode>>,
+    mut pressed: MessageReader<ButtonPressed>,
+    entry_query: Query<&MenuEntry>,
+    character: SelectedCharacter,
+    profile: Res<Profile>,
+    mut saved: ResMut<SavedRun>,
     mut next_state: ResMut<NextState<GameState>>,
+    mut exit: MessageWriter<AppExit>,
 ) {
-    if keys.just_pressed(KeyCode::Space) {
-        next_state.set(GameState::Playing);
+    for ButtonPressed(entity) in pressed.read() {
+        let Ok(entry) = entry_query.get(*entity) else {
+            continue;
+        };
+        match entry {
+            MenuEntry::Continue => {
+                saved.resume = true;
+                next_state.set(GameState::Playing);
+            }
+            // Start with the last character picked, unless it has to be unlocked first
+            MenuEntry::Start if character.get().is_unlocked(&profile) => next_state.set(GameState::Playing),
+            MenuEntry::Start | MenuEntry::Characters => next_state.set(GameState::CharacterSelect),
+            MenuEntry::Upgrades => {}
+            MenuEntry::Settings => next_state.set(GameState::Settings),
+            MenuEntry::HighScores => next_state.set(GameState::HighScores),
+            MenuEntry::Quit => {
+                exit.write(AppExit::Success);
+            }
+        }
     }
 }
 
@@ -170,15 +442,73 @@ fn cleanup_menu(
     }
 }
 
-fn setup_game(mut commands: Commands, asset_server: Res<AssetServer>) {
-    // Spawn player
+/// Despawns everything left over from the previous run when returning to the menu.
+fn cleanup_game(
+    mut commands: Commands,
+    query: Query<Entity, Or<(With<Player>, With<Enemy>, With<Bullet>, With<MeltParticle>, With<GameOverText>)>>,
+    mut transition: ResMut<DeathTransition>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+    *transition = DeathTransition::default();
+}
+
+fn reset_run(
+    mut score: ResMut<Score>,
+    mut stats: ResMut<RunStats>,
+    mut experience: ResMut<Experience>,
+    mut transition: ResMut<DeathTransition>,
+    mut spawn_timer: ResMut<EnemySpawnTimer>,
+    mut rng: ResMut<RunRng>,
+    character: SelectedCharacter,
+) {
+    score.0 = 0;
+    *stats = RunStats {
+        seed: rand::rng().random(),
+        character: character.get().name,
+        ..default()
+    };
+    *rng = RunRng::new(stats.seed);
+    *experience = Experience::default();
+    *transition = DeathTransition::default();
+    spawn_timer.0.reset();
+}
+
+fn track_survival_time(
+    time: Res<Time>,
+    mut stats: ResMut<RunStats>,
+    player_query: Query<(), (With<Player>, Without<Dead>)>,
+) {
+    if !player_query.is_empty() {
+        stats.survival_time += time.delta_secs();
+    }
+}
+
+fn setup_game(mut commands: Commands, tileset: Res<Tileset>, character: SelectedCharacter, archetypes: EnemyArchetypes, mut rng: ResMut<RunRng>) {
+    spawn_player(&mut commands, &tileset, &character.get());
+
+    // Spawn enemies
+    for (id, position) in [("snake", Vec2::new(800.0, 400.0)), ("demon", Vec2::new(-700.0, -500.0))] {
+        if let Some(archetype) = archetypes.get(id) {
+            spawn_enemy(&mut commands, &tileset, &archetype, position, rng.as_mut());
+        }
+    }
+}
+
+/// Spawns the player as the given character at the origin, complete with its health bar.
+fn spawn_player(commands: &mut Commands, tileset: &Tileset, character: &Character) -> Entity {
     commands.spawn((
-        Sprite::from_image(asset_server.load("Colored/tile_0006.png")),
+        tileset.sprite(tileset.index(&character.sprite)),
         Transform::from_xyz(0.0, 0.0, 0.0).with_scale(Vec3::splat(4.0)),
         Player,
-        Speed(200.0),
-        Health { current: 100.0, max: 100.0 },
+        Speed(character.speed),
+        Health { current: character.health, max: character.health },
         LastDirection(Vec3::Y),
+        Animator::new(tileset, &character.animations, &character.sprite),
+        Velocity::default(),
+        Weapon::from(&character.weapon),
+        Passive::new(character.perk),
     )).with_children(|parent| {
         // Health bar background
         parent.spawn((
@@ -199,264 +529,272 @@ fn setup_game(mut commands: Commands, asset_server: Res<AssetServer>) {
             Transform::from_xyz(0.0, 8.0, 0.1),
             HealthBar,
         ));
-    });
-
-    // Spawn enemies
-    commands.spawn((
-        Sprite::from_image(asset_server.load("Colored/tile_0020.png")),
-        Transform::from_xyz(800.0, 400.0, 0.0).with_scale(Vec3::splat(4.0)),
-        Enemy,
-        Health { current: 50.0, max: 50.0 },
-        Damage(10.0),
-        EnemySpeed(50.0),
-    )).with_children(|parent| {
-        // Health bar background
-        parent.spawn((
-            Sprite {
-                color: Color::srgb(0.3, 0.3, 0.3),
-                custom_size: Some(Vec2::new(12.0, 1.5)),
-                ..default()
-            },
-            Transform::from_xyz(0.0, 8.0, 0.0),
-        ));
-        // Health bar foreground
-        parent.spawn((
-            Sprite {
-                color: Color::srgb(0.8, 0.0, 0.0),
-                custom_size: Some(Vec2::new(12.0, 1.5)),
-                ..default()
-            },
-            Transform::from_xyz(0.0, 8.0, 0.1),
-            HealthBar,
-        ));
-    });
-
-    commands.spawn((
-        Sprite::from_image(asset_server.load("Colored/tile_0027.png")),
-        Transform::from_xyz(-700.0, -500.0, 0.0).with_scale(Vec3::splat(4.0)),
-        Enemy,
-        Health { current: 50.0, max: 50.0 },
-        Damage(15.0),
-        EnemySpeed(70.0),
-    )).with_children(|parent| {
-        // Health bar background
-        parent.spawn((
-            Sprite {
-                color: Color::srgb(0.3, 0.3, 0.3),
-                custom_size: Some(Vec2::new(12.0, 1.5)),
-                ..default()
-            },
-            Transform::from_xyz(0.0, 8.0, 0.0),
-        ));
-        // Health bar foreground
-        parent.spawn((
-            Sprite {
-                color: Color::srgb(0.8, 0.0, 0.0),
-                custom_size: Some(Vec2::new(12.0, 1.5)),
-                ..default()
-            },
-            Transform::from_xyz(0.0, 8.0, 0.1),
-            HealthBar,
-        ));
-    });
+    }).id()
+}
 
-    // Spawn score UI
-    commands.spawn((
-        Text::new("Score: 0"),
-        Node {
-            position_type: PositionType::Absolute,
-            top: Val::Px(10.0),
-            right: Val::Px(10.0),
-            ..default()
-        },
-        TextFont {
-            font: asset_server.load("font/BigBlueTerm437NerdFontMono-Regular.ttf"),
-            font_size: 30.0,
-            ..default()
-        },
-        TextColor(Color::WHITE),
-        ScoreText,
-    ));
+fn bullet_sprite(color: Color) -> Sprite {
+    Sprite {
+        color,
+        custom_size: Some(Vec2::new(3.0, 3.0)),
+        ..default()
+    }
 }
 
 fn move_player(
-    keys: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
+    paused: Res<Paused>,
     time: Res<Time>,
     mut query: Query<(&mut Transform, &Speed, &mut LastDirection), (With<Player>, Without<Dead>)>,
 ) {
     if let Ok((mut transform, speed, mut last_dir)) = query.single_mut() {
-        let mut direction = Vec3::ZERO;
+        let direction = actions.move_axis().extend(0.0);
 
-        if keys.pressed(KeyCode::KeyW) { direction.y += 1.0; }
-        if keys.pressed(KeyCode::KeyS) { direction.y -= 1.0; }
-        if keys.pressed(KeyCode::KeyA) { direction.x -= 1.0; }
-        if keys.pressed(KeyCode::KeyD) { direction.x += 1.0; }
-
-        if direction.length() > 0.0 {
-            let normalized = direction.normalize();
-            last_dir.0 = normalized;
-            transform.translation += normalized * speed.0 * time.delta_secs();
+        if direction.length() > 0.0 && !paused.0 {
+            last_dir.0 = direction.normalize();
+            transform.translation += direction * speed.0 * time.delta_secs();
         }
     }
 }
 
 fn move_enemies(
     time: Res<Time>,
+    flow: Res<FlowField>,
     player_query: Query<&Transform, (With<Player>, Without<Enemy>, Without<Dead>)>,
-    mut enemy_query: Query<(&mut Transform, &EnemySpeed), (With<Enemy>, Without<Player>)>,
+    mut enemy_query: Query<(&mut Transform, &EnemySpeed, &mut LastDirection, Option<&StatusEffects>), (With<Enemy>, Without<Player>, Without<Stunned>, Without<Frozen>, Without<Dead>)>,
 ) {
     if let Ok(player_transform) = player_query.single() {
-        for (mut enemy_transform, speed) in enemy_query.iter_mut() {
-            let direction = (player_transform.translation - enemy_transform.translation).normalize();
-            enemy_transform.translation += direction * speed.0 * time.delta_secs();
+        for (mut enemy_transform, speed, mut last_dir, effects) in enemy_query.iter_mut() {
+            let speed = speed.0 * effects.map_or(1.0, StatusEffects::speed_multiplier);
+            // Follow the flow field around obstacles, and head straight for the player where it has no answer
+            let direction = flow
+                .direction(enemy_transform.translation.truncate())
+                .map_or_else(|| (player_transform.translation - enemy_transform.translation).normalize_or_zero(), |direction| direction.extend(0.0));
+            last_dir.0 = direction;
+            enemy_transform.translation += direction * speed * time.delta_secs();
         }
     } else {
         // Player is dead, enemies move away from origin
-        for (mut enemy_transform, speed) in enemy_query.iter_mut() {
+        for (mut enemy_transform, speed, mut last_dir, effects) in enemy_query.iter_mut() {
+            let speed = speed.0 * effects.map_or(1.0, StatusEffects::speed_multiplier);
             let direction = enemy_transform.translation.normalize();
-            enemy_transform.translation += direction * speed.0 * time.delta_secs();
+            last_dir.0 = direction;
+            enemy_transform.translation += direction * speed * time.delta_secs();
         }
     }
 }
 
 fn spawn_enemies(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
+    tileset: Res<Tileset>,
     time: Res<Time>,
     mut timer: ResMut<EnemySpawnTimer>,
-    player_query: Query<&Transform, With<Player>>,
+    player_query: Query<(), (With<Player>, Without<Dead>)>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    grid: Res<WalkabilityGrid>,
+    archetypes: EnemyArchetypes,
+    mut rng: ResMut<RunRng>,
 ) {
     timer.0.tick(time.delta());
     
-    if timer.0.just_finished() {
-        if let Ok(player_transform) = player_query.single() {
-            let mut rng = rand::rng();
-            
-            // Random angle around the player
-            let angle = rng.random_range(0.0..std::f32::consts::TAU);
-            let distance = 800.0; // Spawn distance from player
-            
-            let spawn_x = player_transform.translation.x + angle.cos() * distance;
-            let spawn_y = player_transform.translation.y + angle.sin() * distance;
-            
-            // Random enemy type (3 types now)
-            let enemy_type = rng.random_range(0..3);
-            let (sprite_path, damage, speed, is_shooter) = match enemy_type {
-                0 => ("Colored/tile_0020.png", 10.0, rng.random_range(40.0..60.0), false),
-                1 => ("Colored/tile_0027.png", 15.0, rng.random_range(60.0..80.0), false),
-                _ => ("Colored/tile_0009.png", 5.0, rng.random_range(30.0..40.0), true),
-            };
-            
-            let mut entity = commands.spawn((
-                Sprite::from_image(asset_server.load(sprite_path)),
-                Transform::from_xyz(spawn_x, spawn_y, 0.0).with_scale(Vec3::splat(4.0)),
-                Enemy,
-                Health { current: 50.0, max: 50.0 },
-                Damage(damage),
-                EnemySpeed(speed),
-            ));
-            
-            if is_shooter {
-                entity.insert(ShootingEnemy {
-                    shoot_timer: Timer::from_seconds(2.0, TimerMode::Repeating),
-                });
-            }
-            
-            entity.with_children(|parent| {
-                // Health bar background
-                parent.spawn((
-                    Sprite {
-                        color: Color::srgb(0.3, 0.3, 0.3),
-                        custom_size: Some(Vec2::new(12.0, 1.5)),
-                        ..default()
-                    },
-                    Transform::from_xyz(0.0, 8.0, 0.0),
-                ));
-                // Health bar foreground
-                parent.spawn((
-                    Sprite {
-                        color: Color::srgb(0.8, 0.0, 0.0),
-                        custom_size: Some(Vec2::new(12.0, 1.5)),
-                        ..default()
-                    },
-                    Transform::from_xyz(0.0, 8.0, 0.1),
-                    HealthBar,
-                ));
-            });
+    if timer.0.just_finished() && !player_query.is_empty() {
+        let Ok((camera, camera_transform)) = camera_query.single() else {
+            return;
+        };
+        let Some(view) = visible_area(camera, camera_transform) else {
+            return;
+        };
+
+        let rng = rng.as_mut();
+        // Skip this spawn if every candidate point landed on an obstacle
+        if let Some(position) = offscreen_spawn_point(&grid, view, rng) {
+            let archetype = archetypes.random(rng);
+            spawn_enemy(&mut commands, &tileset, &archetype, position, rng);
         }
     }
 }
 
-fn camera_follow(
-    player_query: Query<&Transform, With<Player>>,
-    mut camera_query: Query<&mut Transform, (With<Camera2d>, Without<Player>)>,
+fn shoot_bullet(
+    mut commands: Commands,
     time: Res<Time>,
+    actions: Res<ActionState>,
+    paused: Res<Paused>,
+    aim_settings: Res<AimSettings>,
+    mut rng: ResMut<RunRng>,
+    mut sounds: MessageWriter<PlaySound>,
+    mut player_query: Query<(&Transform, &LastDirection, &mut Weapon), (With<Player>, Without<Dead>)>,
+    enemy_query: Query<&Transform, (With<Enemy>, Without<Dead>, Without<Player>)>,
 ) {
-    if let (Ok(player_transform), Ok(mut camera_transform)) = (player_query.single(), camera_query.single_mut()) {
-        let target = player_transform.translation;
-        camera_transform.translation = camera_transform.translation.lerp(target, 5.0 * time.delta_secs());
+    let Ok((player_transform, last_direction, mut weapon)) = player_query.single_mut() else {
+        return;
+    };
+    weapon.cooldown.tick(time.delta());
+    weapon.burst_timer.tick(time.delta());
+    if paused.0 {
+        return;
+    }
+
+    // Holding fire starts a burst whenever the weapon is ready; the rest of the burst follows on its own
+    if weapon.burst_remaining > 0 && weapon.burst_timer.is_finished() {
+        weapon.burst_remaining -= 1;
+    } else if actions.pressed(Action::Fire) && weapon.cooldown.is_finished() && weapon.burst_remaining == 0 {
+        weapon.cooldown.reset();
+        weapon.burst_remaining = weapon.pattern.burst.saturating_sub(1);
+    } else {
+        return;
+    }
+    weapon.burst_timer.reset();
+
+    let origin = player_transform.translation.truncate();
+    let auto_aim = aim_settings
+        .auto_aim
+        .then(|| {
+            enemy_query
+                .iter()
+                .map(|enemy| enemy.translation.truncate() - origin)
+                .filter(|offset| offset.length_squared() <= aim_settings.auto_aim_range * aim_settings.auto_aim_range)
+                .min_by(|a, b| a.length_squared().total_cmp(&b.length_squared()))
+                .and_then(|offset| offset.try_normalize())
+        })
+        .flatten();
+    // With no target, the stick at rest or the cursor off screen, shoot the way the player is facing
+    let aim = auto_aim.or_else(|| actions.aim(origin)).unwrap_or(last_direction.0.truncate());
+
+    sounds.write(PlaySound("shoot"));
+    for direction in weapon.pattern.directions(aim) {
+        let direction = direction.extend(0.0);
+        let crit = rng.random::<f32>() < weapon.crit_chance;
+        commands.spawn((
+            bullet_sprite(weapon.color),
+            Transform::from_translation(player_transform.translation + direction * 20.0)
+                .with_scale(Vec3::splat(2.0)),
+            Bullet {
+                velocity: direction * weapon.projectile_speed,
+                damage: if crit { weapon.damage() * CRIT_MULTIPLIER } else { weapon.damage() },
+                crit,
+                on_hit: weapon.on_hit.clone(),
+            },
+        ));
     }
 }
 
-fn shoot_bullet(
+/// Flips auto-aim mid-run, remembering the choice like the settings screen would.
+fn toggle_auto_aim(actions: Res<ActionState>, paused: Res<Paused>, mut settings: ResMut<Settings>) {
+    if actions.just_pressed(Action::AutoAim) && !paused.0 {
+        settings.auto_aim = !settings.auto_aim;
+        settings.save();
+    }
+}
+
+/// Pauses and resumes the run. While paused, Back quits to the menu, which saves the run. Back
+/// wins over Pause for an input bound to both, so Escape pauses and then quits.
+fn pause_input(
     mut commands: Commands,
-    mouse: Res<ButtonInput<MouseButton>>,
-    player_query: Query<&Transform, (With<Player>, Without<Dead>)>,
-    camera_query: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
-    windows: Query<&Window>,
+    actions: Res<ActionState>,
+    bindings: Res<Bindings>,
+    asset_server: Res<AssetServer>,
+    mut pressed: MessageReader<ButtonPressed>,
+    button_query: Query<&PauseButton>,
+    mut paused: ResMut<Paused>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    player_query: Query<(), (With<Player>, Without<Dead>)>,
+    pause_query: Query<Entity, With<PauseUI>>,
+    mut next_state: ResMut<NextState<GameState>>,
 ) {
-    if mouse.just_pressed(MouseButton::Left) {
-        if let (Ok(player_transform), Ok((camera, camera_transform)), Ok(window)) = 
-            (player_query.single(), camera_query.single(), windows.single()) {
-            
-            if let Some(cursor_pos) = window.cursor_position() {
-                // Convert cursor position to world coordinates
-                if let Ok(world_pos) = camera.viewport_to_world_2d(camera_transform, cursor_pos) {
-                    let direction = (world_pos - player_transform.translation.truncate()).normalize().extend(0.0);
-                    
-                    commands.spawn((
-                        Sprite {
-                            color: Color::srgb(1.0, 0.2, 0.0),
-                            custom_size: Some(Vec2::new(3.0, 3.0)),
+    let mut toggle = actions.just_pressed(Action::Pause) && !player_query.is_empty();
+    let mut quit = paused.0 && actions.just_pressed(Action::Back);
+    for ButtonPressed(entity) in pressed.read() {
+        match button_query.get(*entity) {
+            Ok(PauseButton::Resume) => toggle = paused.0,
+            Ok(PauseButton::Quit) => quit = true,
+            Err(_) => {}
+        }
+    }
+
+    if quit {
+        next_state.set(GameState::Menu);
+    } else if toggle {
+        paused.0 = !paused.0;
+        if paused.0 {
+            virtual_time.pause();
+            let font = asset_server.load(widgets::FONT);
+            commands
+                .spawn((widgets::screen(), BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)), ZIndex(1000), PauseUI))
+                .with_children(|parent| {
+                    parent.spawn(widgets::title(&font, "pause.title"));
+                    parent
+                        .spawn(Node {
+                            flex_direction: FlexDirection::Column,
+                            row_gap: Val::Px(8.0),
                             ..default()
-                        },
-                        Transform::from_translation(player_transform.translation + direction * 20.0)
-                            .with_scale(Vec3::splat(2.0)),
-                        Bullet {
-                            velocity: direction * 300.0,
-                            damage: 25.0,
-                        },
+                        })
+                        .with_children(|column| {
+                            column.spawn((widgets::button(&font, "pause.resume"), PauseButton::Resume));
+                            column.spawn((widgets::button(&font, "pause.quit"), PauseButton::Quit));
+                        });
+                    parent.spawn(widgets::hint(
+                        &font,
+                        Localized::new("pause.hint")
+                            .with("pause", bindings.label_without(Action::Pause, Action::Back))
+                            .with("back", bindings.label(Action::Back)),
                     ));
-                }
+                });
+        } else {
+            virtual_time.unpause();
+            for entity in pause_query.iter() {
+                commands.entity(entity).despawn();
             }
         }
     }
 }
 
+/// Leaving a run never leaves the game paused.
+fn end_pause(
+    mut commands: Commands,
+    mut paused: ResMut<Paused>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    pause_query: Query<Entity, With<PauseUI>>,
+) {
+    if paused.0 {
+        paused.0 = false;
+        virtual_time.unpause();
+    }
+    for entity in pause_query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
 fn enemy_shoot_bullets(
     mut commands: Commands,
     time: Res<Time>,
     player_query: Query<&Transform, (With<Player>, Without<Dead>)>,
-    mut enemy_query: Query<(&Transform, &mut ShootingEnemy), Without<Player>>,
+    mut enemy_query: Query<(Entity, &Transform, &mut ShootingEnemy, Option<&mut Animator>), (Without<Player>, Without<Stunned>, Without<Frozen>, Without<Dead>)>,
+    mut animation_events: MessageReader<AnimationEvent>,
 ) {
+    // Enemies with an attack animation shoot on its "fire" frame
+    let firing: Vec<Entity> = animation_events.read().filter(|event| event.name == "fire").map(|event| event.entity).collect();
+
     if let Ok(player_transform) = player_query.single() {
-        for (enemy_transform, mut shooter) in enemy_query.iter_mut() {
+        for (entity, enemy_transform, mut shooter, animator) in enemy_query.iter_mut() {
             shooter.shoot_timer.tick(time.delta());
             
+            let mut fire = firing.contains(&entity);
             if shooter.shoot_timer.just_finished() {
+                let winding_up = animator.is_some_and(|mut animator| animator.play(ClipKind::Attack));
+                fire |= !winding_up;
+            }
+
+            if fire {
                 let direction = (player_transform.translation - enemy_transform.translation).normalize();
                 
                 commands.spawn((
-                    Sprite {
-                        color: Color::srgb(0.8, 0.0, 0.8),
-                        custom_size: Some(Vec2::new(3.0, 3.0)),
-                        ..default()
-                    },
+                    bullet_sprite(ENEMY_BULLET_COLOR),
                     Transform::from_translation(enemy_transform.translation + direction * 20.0)
                         .with_scale(Vec3::splat(2.0)),
                     Bullet {
                         velocity: direction * 200.0,
                         damage: 15.0,
+                        crit: false,
+                        on_hit: OnHit { knockback: 100.0, ..default() },
                     },
                     EnemyBullet,
                 ));
@@ -468,13 +806,16 @@ fn enemy_shoot_bullets(
 fn move_bullets(
     mut commands: Commands,
     mut query: Query<(Entity, &mut Transform, &Bullet)>,
+    player_query: Query<&Transform, (With<Player>, Without<Bullet>)>,
     time: Res<Time>,
 ) {
+    // The streamed world has no edge, so range is measured from the player rather than the origin
+    let center = player_query.single().map_or(Vec3::ZERO, |transform| transform.translation);
     for (entity, mut transform, bullet) in query.iter_mut() {
         transform.translation += bullet.velocity * time.delta_secs();
         
         // Despawn bullets that go too far
-        if transform.translation.length() > 1000.0 {
+        if transform.translation.distance(center) > BULLET_RANGE {
             commands.entity(entity).despawn();
         }
     }
@@ -483,48 +824,42 @@ fn move_bullets(
 fn check_bullet_collisions(
     mut commands: Commands,
     bullet_query: Query<(Entity, &Transform, &Bullet, Option<&EnemyBullet>)>,
-    mut enemy_query: Query<(Entity, &Transform, &mut Health, &Children), With<Enemy>>,
-    mut player_query: Query<(&Transform, &mut Health, &Children), (With<Player>, Without<Enemy>)>,
-    mut score: ResMut<Score>,
+    enemy_query: Query<(Entity, &Transform), (With<Enemy>, Without<Dead>)>,
+    player_query: Query<(Entity, &Transform), (With<Player>, Without<Enemy>)>,
+    mut damage_events: MessageWriter<DamageEvent>,
 ) {
     for (bullet_entity, bullet_transform, bullet, enemy_bullet) in bullet_query.iter() {
         // Enemy bullets hit the player
         if enemy_bullet.is_some() {
-            if let Ok((player_transform, mut player_health, _)) = player_query.single_mut() {
+            if let Ok((player_entity, player_transform)) = player_query.single() {
                 let distance = bullet_transform.translation.distance(player_transform.translation);
-                let collision_distance = 30.0;
-                
-                if distance < collision_distance {
-                    player_health.current = (player_health.current - bullet.damage).max(0.0);
+                if distance < BULLET_HIT_RADIUS {
+                    damage_events.write(DamageEvent {
+                        target: player_entity,
+                        amount: bullet.damage,
+                        crit: bullet.crit,
+                        source: bullet_transform.translation,
+                        on_hit: bullet.on_hit.clone(),
+                        status: None,
+                    });
                     commands.entity(bullet_entity).despawn();
                     break;
                 }
             }
         } else {
             // Player bullets hit enemies
-            for (enemy_entity, enemy_transform, mut health, children) in enemy_query.iter_mut() {
+            for (enemy_entity, enemy_transform) in enemy_query.iter() {
                 let distance = bullet_transform.translation.distance(enemy_transform.translation);
-                let collision_distance = 30.0;
-                
-                if distance < collision_distance {
-                    // Damage enemy
-                    health.current = (health.current - bullet.damage).max(0.0);
-                    
-                    // Despawn bullet
+                if distance < BULLET_HIT_RADIUS {
+                    damage_events.write(DamageEvent {
+                        target: enemy_entity,
+                        amount: bullet.damage,
+                        crit: bullet.crit,
+                        source: bullet_transform.translation,
+                        on_hit: bullet.on_hit.clone(),
+                        status: None,
+                    });
                     commands.entity(bullet_entity).despawn();
-                    
-                    // Check if enemy is dead
-                    if health.current <= 0.0 {
-                        // Increment score
-                        score.0 += 1;
-                        
-                        // Despawn enemy and its children (health bars)
-                        for child in children.iter() {
-                            commands.entity(child).despawn();
-                        }
-                        commands.entity(enemy_entity).despawn();
-                    }
-                    
                     break;
                 }
             }
@@ -532,41 +867,16 @@ fn check_bullet_collisions(
     }
 }
 
-fn update_score_text(
-    score: Res<Score>,
-    mut query: Query<&mut Text, With<ScoreText>>,
-) {
-    if score.is_changed() {
-        for mut text in query.iter_mut() {
-            **text = format!("Score: {}", score.0);
-        }
-    }
-}
-
-fn update_particles(
-    mut commands: Commands,
-    mut query: Query<(Entity, &mut Transform, &mut Particle)>,
-    time: Res<Time>,
-) {
-    for (entity, mut transform, mut particle) in query.iter_mut() {
-        particle.lifetime.tick(time.delta());
-        
-        if particle.lifetime.is_finished() {
-            commands.entity(entity).despawn();
-        } else {
-            transform.translation += particle.velocity * time.delta_secs();
-            particle.velocity *= 0.98; // Slow down over time
-        }
-    }
-}
-
 fn update_health_bars(
-    health_query: Query<(&Health, &Children)>,
+    accessibility: Res<AccessibilitySettings>,
+    health_query: Query<(&Health, &Children, Has<Player>)>,
     mut bar_query: Query<(&mut Transform, &mut Sprite), With<HealthBar>>,
 ) {
-    for (health, children) in health_query.iter() {
+    let colors = accessibility.palette.colors();
+    for (health, children, is_player) in health_query.iter() {
         for child in children.iter() {
             if let Ok((mut transform, mut sprite)) = bar_query.get_mut(child) {
+                sprite.color = if is_player { colors.player_health } else { colors.enemy_health };
                 let health_percent = health.current / health.max;
                 if let Some(size) = sprite.custom_size.as_mut() {
                     size.x = 12.0 * health_percent;
@@ -578,10 +888,12 @@ fn update_health_bars(
 }
 
 fn check_collisions(
-    mut player_query: Query<(&Transform, &mut Health), With<Player>>,
-    enemy_query: Query<(&Transform, &Damage), With<Enemy>>,
+    player_query: Query<(Entity, &Transform), (With<Player>, Without<Dead>)>,
+    enemy_query: Query<(Entity, &Transform, &Damage), (With<Enemy>, Without<Dead>)>,
     mut cooldown: ResMut<CollisionCooldown>,
     time: Res<Time>,
+    mut damage_events: MessageWriter<DamageEvent>,
+    mut impulses: MessageWriter<Impulse>,
 ) {
     cooldown.0.tick(time.delta());
     
@@ -589,14 +901,82 @@ fn check_collisions(
         return;
     }
 
-    if let Ok((player_transform, mut player_health)) = player_query.single_mut() {
-        for (enemy_transform, damage) in enemy_query.iter() {
+    if let Ok((player_entity, player_transform)) = player_query.single() {
+        for (enemy_entity, enemy_transform, damage) in enemy_query.iter() {
             let distance = player_transform.translation.distance(enemy_transform.translation);
-            let collision_distance = 40.0; // Approximate sprite size * scale
-            
-            if distance < collision_distance {
-                player_health.current = (player_health.current - damage.0).max(0.0);
-                println!("Player hit! Health: {}/{}", player_health.current, player_health.max);
+            if distance < CONTACT_RADIUS {
+                damage_events.write(DamageEvent {
+                    target: player_entity,
+                    amount: damage.0,
+                    crit: false,
+                    source: enemy_transform.translation,
+                    on_hit: OnHit { knockback: CONTACT_KNOCKBACK, ..default() },
+                    status: None,
+                });
+                // The enemy bounces off the player as well
+                let recoil = (enemy_transform.translation - player_transform.translation).truncate().normalize_or_zero();
+                impulses.write(Impulse { target: enemy_entity, impulse: recoil * CONTACT_KNOCKBACK });
+            }
+        }
+    }
+}
+
+/// Applies queued damage, handling armor for the player and kills for enemies.
+fn apply_damage(
+    mut commands: Commands,
+    mut damage_events: MessageReader<DamageEvent>,
+    mut damage_dealt: MessageWriter<DamageDealt>,
+    mut health_query: Query<(&mut Health, &Transform, Option<&Passive>, Has<Player>, Has<Animator>)>,
+    mut score: ResMut<Score>,
+    mut stats: ResMut<RunStats>,
+    mut experience: ResMut<Experience>,
+) {
+    let mut kills = 0;
+
+    for event in damage_events.read() {
+        let Ok((mut health, transform, passive, is_player, animated)) = health_query.get_mut(event.target) else {
+            continue;
+        };
+        // Already dead and waiting to be despawned
+        if health.current <= 0.0 {
+            continue;
+        }
+
+        let amount = if is_player { Passive::reduce_damage(passive, event.amount) } else { event.amount };
+        health.current = (health.current - amount).max(0.0);
+        damage_dealt.write(DamageDealt {
+            target: event.target,
+            amount,
+            crit: event.crit,
+            position: transform.translation,
+            direction: (transform.translation - event.source).truncate().normalize_or_zero(),
+            to_player: is_player,
+            killed: health.current <= 0.0,
+            on_hit: event.on_hit.clone(),
+            status: event.status,
+        });
+
+        if !is_player && health.current <= 0.0 {
+            score.0 += 1;
+            stats.kills += 1;
+            experience.xp += 1;
+            kills += 1;
+
+            if animated {
+                // Drop the health bar and let the death animation play before despawning
+                commands.entity(event.target).insert(Dead).despawn_related::<Children>();
+            } else {
+                // Despawn enemy and its children (health bars)
+                commands.entity(event.target).despawn();
+            }
+        }
+    }
+
+    if kills > 0 {
+        for (mut health, _, passive, is_player, _) in health_query.iter_mut() {
+            if is_player && health.current > 0.0 {
+                let heal = Passive::heal_on_kill(passive) * kills as f32;
+                health.current = (health.current + heal).min(health.max);
             }
         }
     }
@@ -606,6 +986,7 @@ fn update_death_transition(
     time: Res<Time>,
     mut transition: ResMut<DeathTransition>,
     mut melt_query: Query<(&mut Sprite, &mut MeltParticle)>,
+    mut next_state: ResMut<NextState<GameState>>,
 ) {
     if !transition.active {
         return;
@@ -627,26 +1008,28 @@ fn update_death_transition(
     
     if transition.timer.just_finished() {
         transition.active = false;
+        next_state.set(GameState::GameOver);
     }
 }
 
-/// If player health is zero, mark as dead, change sprite, spawn melting effect and explosion particles.
+/// If player health is zero, mark as dead, change sprite, start the spiral and melting effect and spawn explosion particles.
 fn check_death(
     mut commands: Commands,
-    mut player_query: Query<(Entity, &Transform, &Health, &mut Sprite), (With<Player>, Without<Dead>)>,
+    player_query: Query<(Entity, &Transform, &Health), (With<Player>, Without<Dead>)>,
     game_over_query: Query<Entity, With<GameOverText>>,
     asset_server: Res<AssetServer>,
     mut transition: ResMut<DeathTransition>,
+    accessibility: Res<AccessibilitySettings>,
     windows: Query<&Window>,
+    mut explosions: MessageWriter<Explosion>,
+    mut particles: MessageWriter<EmitParticles>,
+    mut sounds: MessageWriter<PlaySound>,
 ) {
-    if let Ok((player_entity, transform, health, mut sprite)) = player_query.single_mut() {
+    if let Ok((player_entity, transform, health)) = player_query.single() {
         if health.current <= 0.0 && game_over_query.is_empty() {
-            // Mark player as dead
+            // Mark player as dead, which plays the death animation
             commands.entity(player_entity).insert(Dead);
             
-            // Change sprite to death sprite
-            sprite.image = asset_server.load("Colored/tile_0120.png");
-            
             // Start death transition
             transition.active = true;
             transition.timer.reset();
@@ -660,7 +1043,8 @@ fn check_death(
             
             // Spawn melting black circles across the screen
             let mut rng = rand::rng();
-            for _ in 0..30 {
+            let circles = if accessibility.reduced_motion { 0 } else { 30 };
+            for _ in 0..circles {
                 let x = rng.random::<f32>() * width - width / 2.0;
                 let y = rng.random::<f32>() * height - height / 2.0;
                 let max_radius = 80.0 + rng.random::<f32>() * 100.0;
@@ -679,38 +1063,23 @@ fn check_death(
                 ));
             }
             
-            // Spawn explosion particles
-            for _ in 0..50 {
-                let angle = rng.random::<f32>() * std::f32::consts::TAU;
-                let speed = 100.0 + rng.random::<f32>() * 100.0;
-                let velocity = Vec3::new(angle.cos(), angle.sin(), 0.0) * speed;
-                
-                commands.spawn((
-                    Sprite {
-                        color: Color::srgb(1.0, rng.random::<f32>() * 0.3, 0.0),
-                        custom_size: Some(Vec2::new(2.0, 2.0)),
-                        ..default()
-                    },
-                    Transform::from_translation(transform.translation),
-                    Particle {
-                        velocity,
-                        lifetime: Timer::from_seconds(1.0, TimerMode::Once),
-                    },
-                ));
-            }
+            // Spawn explosion particles and blow nearby enemies away
+            explosions.write(Explosion { position: transform.translation.truncate(), radius: 250.0, force: 800.0 });
+            particles.write(EmitParticles::at("player_death", transform.translation.truncate()));
+            sounds.write(PlaySound("game_over"));
             
             // Spawn Game Over UI immediately on top
             commands.spawn((
-                Text::new("GAME OVER\n\nPress SPACE for Menu"),
+                Localized::new("game_over.title"),
                 Node {
                     position_type: PositionType::Absolute,
-                    top: Val::Percent(35.0),
+                    top: Val::Percent(12.0),
                     width: Val::Percent(100.0),
                     justify_content: JustifyContent::Center,
                     ..default()
                 },
                 TextFont {
-                    font: asset_server.load("font/BigBlueTerm437NerdFontMono-Regular.ttf"),
+                    font: asset_server.load(widgets::FONT),
                     font_size: 60.0,
                     ..default()
                 },
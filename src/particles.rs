@@ -0,0 +1,350 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::data::RonAssetLoader;
+use crate::progression::LevelUp;
+use crate::{DamageDealt, GameState, Player};
+
+// Particle Plugin
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<ParticleEffects>()
+            .register_asset_loader(RonAssetLoader::<ParticleEffects>::default())
+            .init_resource::<ParticlePool>()
+            .add_message::<EmitParticles>()
+            .add_systems(Startup, load_particle_effects)
+            .add_systems(
+                Update,
+                (trigger_particles, spawn_emitters, run_emitters, update_particles).chain().run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(OnEnter(GameState::Menu), release_particles);
+    }
+}
+
+/// Most particles alive at once. Bursts past this are cut short rather than spawning more.
+const MAX_PARTICLES: usize = 2048;
+
+/// Named particle effects, loaded from `assets/data/particles.ron`.
+#[derive(Asset, TypePath, Deserialize)]
+pub struct ParticleEffects {
+    pub effects: HashMap<String, EmitterDef>,
+}
+
+impl Default for ParticleEffects {
+    /// Used if the effects asset fails to load.
+    fn default() -> Self {
+        let effects = [
+            ("hit", EmitterDef { burst: 5, lifetime: (0.15, 0.3), speed: (80.0, 160.0), spread: 70.0, damping: 6.0, ..default() }),
+            ("crit", EmitterDef { burst: 10, lifetime: (0.2, 0.4), speed: (120.0, 220.0), spread: 90.0, damping: 6.0, colors: vec![[1.0, 0.85, 0.1, 1.0], [1.0, 0.5, 0.0, 0.0]], ..default() }),
+            ("player_hit", EmitterDef { burst: 8, lifetime: (0.2, 0.4), speed: (80.0, 160.0), spread: 90.0, damping: 6.0, colors: vec![[1.0, 0.25, 0.25, 1.0], [0.5, 0.0, 0.0, 0.0]], ..default() }),
+            ("enemy_death", EmitterDef { burst: 14, lifetime: (0.3, 0.6), speed: (60.0, 140.0), spread: 360.0, damping: 4.0, colors: vec![[0.9, 0.9, 0.9, 1.0], [0.4, 0.4, 0.4, 0.0]], sizes: vec![3.0, 1.0], ..default() }),
+            ("player_death", EmitterDef { burst: 50, lifetime: (1.0, 1.0), speed: (100.0, 200.0), spread: 360.0, damping: 1.2, colors: vec![[1.0, 0.3, 0.0, 1.0], [1.0, 0.0, 0.0, 1.0]], ..default() }),
+            ("level_up", EmitterDef { burst: 12, rate: 40.0, duration: 0.6, lifetime: (0.5, 0.8), speed: (40.0, 90.0), angle: 90.0, spread: 120.0, damping: 1.0, colors: vec![[0.4, 0.9, 1.0, 1.0], [1.0, 1.0, 1.0, 0.0]], sizes: vec![3.0, 1.0] }),
+        ];
+        Self { effects: effects.into_iter().map(|(name, def)| (name.to_string(), def)).collect() }
+    }
+}
+
+/// How an effect emits its particles.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct EmitterDef {
+    /// Particles emitted all at once when the effect is triggered.
+    pub burst: u32,
+    /// Particles emitted per second for `duration` seconds afterwards.
+    pub rate: f32,
+    pub duration: f32,
+    /// Lifetime in seconds, picked at random from this range.
+    pub lifetime: (f32, f32),
+    /// Starting speed in pixels per second, picked at random from this range.
+    pub speed: (f32, f32),
+    /// Direction in degrees (0 is right, 90 is up), used when the trigger gives none.
+    pub angle: f32,
+    /// Width of the cone particles are launched in, in degrees. 360 sends them every way.
+    pub spread: f32,
+    /// Exponential slowdown per second.
+    pub damping: f32,
+    /// Colors as sRGB(A), spread evenly over each particle's life.
+    pub colors: Vec<[f32; 4]>,
+    /// Sizes in pixels, spread evenly over each particle's life.
+    pub sizes: Vec<f32>,
+}
+
+impl Default for EmitterDef {
+    fn default() -> Self {
+        Self {
+            burst: 0,
+            rate: 0.0,
+            duration: 0.0,
+            lifetime: (0.5, 0.5),
+            speed: (100.0, 100.0),
+            angle: 0.0,
+            spread: 360.0,
+            damping: 0.0,
+            colors: vec![[1.0, 1.0, 1.0, 1.0]],
+            sizes: vec![2.0],
+        }
+    }
+}
+
+impl EmitterDef {
+    fn color_at(&self, t: f32) -> Color {
+        let [r, g, b, a] = sample(&self.colors, t, |a, b, t| std::array::from_fn(|i| a[i] + (b[i] - a[i]) * t)).unwrap_or([1.0; 4]);
+        Color::srgba(r, g, b, a)
+    }
+
+    fn size_at(&self, t: f32) -> f32 {
+        sample(&self.sizes, t, |a, b, t| a + (b - a) * t).unwrap_or(2.0)
+    }
+}
+
+/// Interpolates between evenly spaced keys at `t` from 0 to 1.
+fn sample<T: Copy>(keys: &[T], t: f32, lerp: impl Fn(T, T, f32) -> T) -> Option<T> {
+    match keys {
+        [] => None,
+        [only] => Some(*only),
+        _ => {
+            let position = t.clamp(0.0, 1.0) * (keys.len() - 1) as f32;
+            let index = (position as usize).min(keys.len() - 2);
+            Some(lerp(keys[index], keys[index + 1], position - index as f32))
+        }
+    }
+}
+
+/// Plays a named effect from `particles.ron`.
+#[derive(Message)]
+pub struct EmitParticles {
+    pub effect: &'static str,
+    pub position: Vec2,
+    /// Center of the launch cone. Defaults to the effect's `angle`.
+    pub direction: Option<Vec2>,
+    /// Keeps a continuous effect on this entity while it lasts.
+    pub follow: Option<Entity>,
+}
+
+impl EmitParticles {
+    pub fn at(effect: &'static str, position: Vec2) -> Self {
+        Self { effect, position, direction: None, follow: None }
+    }
+}
+
+#[derive(Resource)]
+struct ParticleEffectsHandle(Handle<ParticleEffects>);
+
+fn load_particle_effects(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(ParticleEffectsHandle(asset_server.load("data/particles.ron")));
+}
+
+#[derive(Component)]
+pub struct Particle {
+    effect: Arc<EmitterDef>,
+    velocity: Vec2,
+    age: f32,
+    lifetime: f32,
+}
+
+/// Emits an effect's `rate` for its `duration`, then despawns.
+#[derive(Component)]
+struct ParticleEmitter {
+    effect: Arc<EmitterDef>,
+    direction: Option<Vec2>,
+    follow: Option<Entity>,
+    timer: Timer,
+    /// Fractional particles carried over between frames.
+    pending: f32,
+}
+
+/// Particle entities are reused instead of being spawned for every burst.
+#[derive(Resource, Default)]
+struct ParticlePool {
+    free: Vec<Entity>,
+    active: usize,
+}
+
+impl ParticlePool {
+    fn emit(&mut self, commands: &mut Commands, effect: &Arc<EmitterDef>, position: Vec2, direction: Option<Vec2>, rng: &mut impl Rng) {
+        if self.active >= MAX_PARTICLES {
+            return;
+        }
+        self.active += 1;
+
+        let center = direction.filter(|direction| *direction != Vec2::ZERO).map_or(effect.angle.to_radians(), |direction| direction.to_angle());
+        let half_spread = effect.spread.to_radians() / 2.0;
+        let angle = center + if half_spread > 0.0 { rng.random_range(-half_spread..=half_spread) } else { 0.0 };
+        let speed = random_in(effect.speed, rng);
+        let particle = Particle {
+            effect: effect.clone(),
+            velocity: Vec2::from_angle(angle) * speed,
+            age: 0.0,
+            lifetime: random_in(effect.lifetime, rng).max(0.01),
+        };
+        let size = effect.size_at(0.0);
+        let bundle = (
+            Sprite { color: effect.color_at(0.0), custom_size: Some(Vec2::splat(size)), ..default() },
+            Transform::from_translation(position.extend(5.0)),
+            Visibility::Inherited,
+            particle,
+        );
+        match self.free.pop() {
+            Some(entity) => {
+                commands.entity(entity).insert(bundle);
+            }
+            None => {
+                commands.spawn(bundle);
+            }
+        }
+    }
+}
+
+fn random_in((min, max): (f32, f32), rng: &mut impl Rng) -> f32 {
+    if min < max { rng.random_range(min..max) } else { min }
+}
+
+/// Turns gameplay messages into particle effects.
+fn trigger_particles(
+    mut damage_dealt: MessageReader<DamageDealt>,
+    mut level_ups: MessageReader<LevelUp>,
+    player_query: Query<(Entity, &Transform), With<Player>>,
+    mut emit: MessageWriter<EmitParticles>,
+) {
+    for hit in damage_dealt.read() {
+        // Status ticks have their own tint and numbers, and the player's death is handled by `check_death`
+        if hit.status.is_some() || (hit.to_player && hit.killed) {
+            continue;
+        }
+        let effect = match (hit.to_player, hit.killed, hit.crit) {
+            (true, _, _) => "player_hit",
+            (false, true, _) => "enemy_death",
+            (false, false, true) => "crit",
+            (false, false, false) => "hit",
+        };
+        emit.write(EmitParticles { effect, position: hit.position.truncate(), direction: Some(hit.direction), follow: None });
+    }
+
+    if level_ups.read().count() > 0 {
+        if let Ok((player, transform)) = player_query.single() {
+            emit.write(EmitParticles { follow: Some(player), ..EmitParticles::at("level_up", transform.translation.truncate()) });
+        }
+    }
+}
+
+fn spawn_emitters(
+    mut commands: Commands,
+    mut emit: MessageReader<EmitParticles>,
+    handle: Res<ParticleEffectsHandle>,
+    effects: Res<Assets<ParticleEffects>>,
+    mut pool: ResMut<ParticlePool>,
+) {
+    let fallback;
+    let effects = match effects.get(&handle.0) {
+        Some(effects) => effects,
+        None => {
+            fallback = ParticleEffects::default();
+            &fallback
+        }
+    };
+    let mut rng = rand::rng();
+
+    for request in emit.read() {
+        let Some(def) = effects.effects.get(request.effect) else {
+            warn!("Unknown particle effect {:?}", request.effect);
+            continue;
+        };
+        let effect = Arc::new(def.clone());
+        for _ in 0..effect.burst {
+            pool.emit(&mut commands, &effect, request.position, request.direction, &mut rng);
+        }
+        if effect.rate > 0.0 && effect.duration > 0.0 {
+            commands.spawn((
+                Transform::from_translation(request.position.extend(0.0)),
+                ParticleEmitter {
+                    timer: Timer::from_seconds(effect.duration, TimerMode::Once),
+                    effect,
+                    direction: request.direction,
+                    follow: request.follow,
+                    pending: 0.0,
+                },
+            ));
+        }
+    }
+}
+
+fn run_emitters(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut pool: ResMut<ParticlePool>,
+    mut emitter_query: Query<(Entity, &mut Transform, &mut ParticleEmitter)>,
+    target_query: Query<&Transform, Without<ParticleEmitter>>,
+) {
+    let mut rng = rand::rng();
+    for (entity, mut transform, mut emitter) in emitter_query.iter_mut() {
+        if let Some(target) = emitter.follow.and_then(|target| target_query.get(target).ok()) {
+            transform.translation = target.translation.with_z(0.0);
+        }
+
+        emitter.timer.tick(time.delta());
+        emitter.pending += emitter.effect.rate * time.delta_secs();
+        while emitter.pending >= 1.0 {
+            emitter.pending -= 1.0;
+            pool.emit(&mut commands, &emitter.effect, transform.translation.truncate(), emitter.direction, &mut rng);
+        }
+
+        if emitter.timer.is_finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn update_particles(
+    time: Res<Time>,
+    mut pool: ResMut<ParticlePool>,
+    mut query: Query<(Entity, &mut Particle, &mut Transform, &mut Sprite, &mut Visibility)>,
+) {
+    let dt = time.delta_secs();
+    for (entity, mut particle, mut transform, mut sprite, mut visibility) in query.iter_mut() {
+        if *visibility == Visibility::Hidden {
+            continue;
+        }
+
+        particle.age += dt;
+        if particle.age >= particle.lifetime {
+            *visibility = Visibility::Hidden;
+            pool.active -= 1;
+            pool.free.push(entity);
+            continue;
+        }
+
+        transform.translation += (particle.velocity * dt).extend(0.0);
+        let damping = particle.effect.damping;
+        particle.velocity *= (-damping * dt).exp();
+
+        let t = particle.age / particle.lifetime;
+        sprite.color = particle.effect.color_at(t);
+        sprite.custom_size = Some(Vec2::splat(particle.effect.size_at(t)));
+    }
+}
+
+/// Hides every particle and stops every emitter when the run ends.
+fn release_particles(
+    mut commands: Commands,
+    mut pool: ResMut<ParticlePool>,
+    mut particle_query: Query<(Entity, &mut Visibility), With<Particle>>,
+    emitter_query: Query<Entity, With<ParticleEmitter>>,
+) {
+    for (entity, mut visibility) in particle_query.iter_mut() {
+        if *visibility != Visibility::Hidden {
+            *visibility = Visibility::Hidden;
+            pool.free.push(entity);
+        }
+    }
+    pool.active = 0;
+    for entity in emitter_query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
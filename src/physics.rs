@@ -0,0 +1,172 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::status::StatusApplication;
+use crate::{DamageDealt, DamageSystems, GameState};
+
+// Physics Plugin
+pub struct PhysicsPlugin;
+
+impl Plugin for PhysicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<Impulse>()
+            .add_message::<Explosion>()
+            .add_systems(
+                Update,
+                (apply_hit_effects, apply_explosions, apply_impulses, integrate_velocity, tick_stuns)
+                    .chain()
+                    .in_set(DamageSystems::React)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// Velocity from knockback and other impulses, on top of whatever an entity does on its own.
+#[derive(Component, Default)]
+#[require(Damping, Mass)]
+pub struct Velocity(pub Vec2);
+
+/// Exponential decay rate of `Velocity` per second, so it slows the same at any frame rate.
+#[derive(Component)]
+pub struct Damping(pub f32);
+
+impl Default for Damping {
+    fn default() -> Self {
+        Self(8.0)
+    }
+}
+
+/// Impulses are divided by mass, so heavy entities barely budge.
+#[derive(Component)]
+pub struct Mass(pub f32);
+
+impl Default for Mass {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// Stops an enemy from moving or attacking on its own. It can still be pushed around.
+#[derive(Component)]
+pub struct Stunned(pub Timer);
+
+/// Locked in place and unaffected by impulses. Kept in sync with the `Freeze` status effect.
+#[derive(Component)]
+pub struct Frozen;
+
+/// What a hit does besides damage.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Default, Debug)]
+#[serde(default)]
+pub struct OnHit {
+    /// Impulse applied away from the damage source.
+    pub knockback: f32,
+    /// Seconds the target is stunned for.
+    pub stun: f32,
+    /// Status effects the hit may inflict.
+    pub status: Vec<StatusApplication>,
+}
+
+#[derive(Message)]
+pub struct Impulse {
+    pub target: Entity,
+    pub impulse: Vec2,
+}
+
+/// Pushes everything with a `Velocity` away from a point, strongest at the center.
+#[derive(Message)]
+pub struct Explosion {
+    pub position: Vec2,
+    pub radius: f32,
+    pub force: f32,
+}
+
+fn apply_hit_effects(
+    mut commands: Commands,
+    mut damage_dealt: MessageReader<DamageDealt>,
+    mut impulses: MessageWriter<Impulse>,
+    mut stun_query: Query<Option<&mut Stunned>>,
+) {
+    for hit in damage_dealt.read() {
+        if hit.on_hit.knockback > 0.0 {
+            impulses.write(Impulse { target: hit.target, impulse: hit.direction * hit.on_hit.knockback });
+        }
+
+        let Ok(stunned) = stun_query.get_mut(hit.target) else {
+            continue;
+        };
+        // Reapplying only ever extends the remaining duration
+        if hit.on_hit.stun > 0.0 {
+            match stunned {
+                Some(stunned) if stunned.0.remaining_secs() >= hit.on_hit.stun => {}
+                Some(mut stunned) => stunned.0 = Timer::from_seconds(hit.on_hit.stun, TimerMode::Once),
+                None => {
+                    commands.entity(hit.target).try_insert(Stunned(Timer::from_seconds(hit.on_hit.stun, TimerMode::Once)));
+                }
+            }
+        }
+    }
+}
+
+fn apply_explosions(
+    mut explosions: MessageReader<Explosion>,
+    mut impulses: MessageWriter<Impulse>,
+    query: Query<(Entity, &Transform), With<Velocity>>,
+) {
+    for explosion in explosions.read() {
+        for (entity, transform) in query.iter() {
+            let offset = transform.translation.truncate() - explosion.position;
+            let distance = offset.length();
+            if distance < explosion.radius {
+                let falloff = 1.0 - distance / explosion.radius;
+                impulses.write(Impulse { target: entity, impulse: offset.normalize_or_zero() * explosion.force * falloff });
+            }
+        }
+    }
+}
+
+fn apply_impulses(
+    mut impulses: MessageReader<Impulse>,
+    mut query: Query<(&mut Velocity, &Mass), Without<Frozen>>,
+) {
+    for impulse in impulses.read() {
+        if let Ok((mut velocity, mass)) = query.get_mut(impulse.target) {
+            velocity.0 += impulse.impulse / mass.0.max(0.1);
+        }
+    }
+}
+
+fn integrate_velocity(
+    time: Res<Time>,
+    mut query: Query<(&mut Transform, &mut Velocity, &Damping)>,
+) {
+    for (mut transform, mut velocity, damping) in query.iter_mut() {
+        if velocity.0 == Vec2::ZERO {
+            continue;
+        }
+
+        transform.translation += velocity.0.extend(0.0) * time.delta_secs();
+        velocity.0 *= (-damping.0 * time.delta_secs()).exp();
+
+        if velocity.0.length_squared() < 1.0 {
+            velocity.0 = Vec2::ZERO;
+        }
+    }
+}
+
+fn tick_stuns(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut stunned_query: Query<(Entity, &mut Stunned)>,
+    mut frozen_query: Query<&mut Velocity, With<Frozen>>,
+) {
+    for (entity, mut stunned) in stunned_query.iter_mut() {
+        stunned.0.tick(time.delta());
+        if stunned.0.is_finished() {
+            commands.entity(entity).try_remove::<Stunned>();
+        }
+    }
+
+    for mut velocity in frozen_query.iter_mut() {
+        velocity.0 = Vec2::ZERO;
+    }
+}
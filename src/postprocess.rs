@@ -0,0 +1,346 @@
+use std::marker::PhantomData;
+
+use bevy::core_pipeline::FullscreenShader;
+use bevy::core_pipeline::core_2d::graph::{Core2d, Node2d};
+use bevy::ecs::query::QueryItem;
+use bevy::image::BevyDefault;
+use bevy::prelude::*;
+use bevy::render::extract_component::{ComponentUniforms, DynamicUniformIndex, ExtractComponent, ExtractComponentPlugin, UniformComponentPlugin};
+use bevy::render::render_graph::{InternedRenderLabel, NodeRunError, RenderGraphContext, RenderGraphExt, RenderLabel, ViewNode, ViewNodeRunner};
+use bevy::render::render_resource::binding_types::{sampler, texture_2d, uniform_buffer};
+use bevy::render::render_resource::encase::internal::WriteInto;
+use bevy::render::render_resource::{
+    BindGroupEntries, BindGroupLayout, BindGroupLayoutEntries, CachedRenderPipelineId, ColorTargetState, ColorWrites, FragmentState,
+    Operations, PipelineCache, RenderPassColorAttachment, RenderPassDescriptor, RenderPipelineDescriptor, Sampler, SamplerBindingType,
+    SamplerDescriptor, ShaderStages, ShaderType, TextureFormat, TextureSampleType,
+};
+use bevy::render::renderer::{RenderContext, RenderDevice};
+use bevy::render::view::ViewTarget;
+use bevy::render::{RenderApp, RenderStartup};
+
+use crate::{DamageDealt, GameState, Health, Paused, Player};
+
+// Post Process Plugin
+pub struct PostProcessPlugin;
+
+impl Plugin for PostProcessPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(FullscreenEffectPlugin::<ScreenEffects>::default())
+            .init_resource::<ScreenEffectSettings>()
+            .init_resource::<HitStop>()
+            .add_systems(Update, (start_hit_stop, update_hit_stop, animate_screen_effects).chain())
+            .add_systems(OnExit(GameState::Playing), end_hit_stop);
+    }
+}
+
+/// A fullscreen shader that runs over every camera with the component, taking the component
+/// as its uniform. The shader binds the screen texture, a sampler and the uniform, in that order.
+pub trait FullscreenEffect: Component + ShaderType + WriteInto + Clone + ExtractComponent<Out = Self> {
+    const SHADER: &'static str;
+    /// Names the render graph node.
+    const LABEL: &'static str;
+}
+
+/// Adds a fullscreen effect to the 2D render graph after tonemapping. Effects run in the order
+/// their plugins are added.
+pub struct FullscreenEffectPlugin<E>(PhantomData<E>);
+
+impl<E> Default for FullscreenEffectPlugin<E> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+#[derive(RenderLabel, Debug, Hash, PartialEq, Eq, Clone)]
+struct EffectLabel(&'static str);
+
+/// The most recently added effect, which the next one is chained after.
+#[derive(Resource)]
+struct LastEffect(InternedRenderLabel);
+
+impl<E: FullscreenEffect> Plugin for FullscreenEffectPlugin<E> {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((ExtractComponentPlugin::<E>::default(), UniformComponentPlugin::<E>::default()));
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        let label = EffectLabel(E::LABEL);
+        let previous = render_app.world().get_resource::<LastEffect>().map_or(Node2d::Tonemapping.intern(), |last| last.0);
+        render_app
+            .insert_resource(LastEffect(label.intern()))
+            .add_systems(RenderStartup, init_effect_pipeline::<E>)
+            .add_render_graph_node::<ViewNodeRunner<EffectNode<E>>>(Core2d, label.clone())
+            .add_render_graph_edge(Core2d, previous, label.clone())
+            .add_render_graph_edge(Core2d, label, Node2d::EndMainPassPostProcessing);
+    }
+}
+
+#[derive(Resource)]
+struct EffectPipeline<E> {
+    layout: BindGroupLayout,
+    sampler: Sampler,
+    pipeline_id: CachedRenderPipelineId,
+    marker: PhantomData<E>,
+}
+
+fn init_effect_pipeline<E: FullscreenEffect>(
+    mut commands: Commands,
+    render_device: Res<RenderDevice>,
+    asset_server: Res<AssetServer>,
+    fullscreen_shader: Res<FullscreenShader>,
+    pipeline_cache: Res<PipelineCache>,
+) {
+    let layout = render_device.create_bind_group_layout(
+        E::LABEL,
+        &BindGroupLayoutEntries::sequential(
+            ShaderStages::FRAGMENT,
+            (
+                texture_2d(TextureSampleType::Float { filterable: true }),
+                sampler(SamplerBindingType::Filtering),
+                uniform_buffer::<E>(true),
+            ),
+        ),
+    );
+    let sampler = render_device.create_sampler(&SamplerDescriptor::default());
+    let pipeline_id = pipeline_cache.queue_render_pipeline(RenderPipelineDescriptor {
+        label: Some(E::LABEL.into()),
+        layout: vec![layout.clone()],
+        vertex: fullscreen_shader.to_vertex_state(),
+        fragment: Some(FragmentState {
+            shader: asset_server.load(E::SHADER),
+            targets: vec![Some(ColorTargetState { format: TextureFormat::bevy_default(), blend: None, write_mask: ColorWrites::ALL })],
+            ..default()
+        }),
+        ..default()
+    });
+
+    commands.insert_resource(EffectPipeline::<E> { layout, sampler, pipeline_id, marker: PhantomData });
+}
+
+/// Runs an effect's shader over cameras that extracted its settings this frame.
+struct EffectNode<E>(PhantomData<E>);
+
+impl<E> Default for EffectNode<E> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<E: FullscreenEffect> ViewNode for EffectNode<E> {
+    type ViewQuery = (&'static ViewTarget, &'static DynamicUniformIndex<E>);
+
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (view_target, settings_index): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let effect_pipeline = world.resource::<EffectPipeline<E>>();
+        let Some(pipeline) = world.resource::<PipelineCache>().get_render_pipeline(effect_pipeline.pipeline_id) else {
+            return Ok(());
+        };
+        let Some(settings_binding) = world.resource::<ComponentUniforms<E>>().uniforms().binding() else {
+            return Ok(());
+        };
+
+        let post_process = view_target.post_process_write();
+        let bind_group = render_context.render_device().create_bind_group(
+            E::LABEL,
+            &effect_pipeline.layout,
+            &BindGroupEntries::sequential((post_process.source, &effect_pipeline.sampler, settings_binding.clone())),
+        );
+
+        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some(E::LABEL),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: post_process.destination,
+                depth_slice: None,
+                resolve_target: None,
+                ops: Operations::default(),
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_render_pipeline(pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[settings_index.index()]);
+        render_pass.draw(0..3, 0..1);
+
+        Ok(())
+    }
+}
+
+/// Which screen effects are shown, and how strongly.
+#[derive(Resource)]
+pub struct ScreenEffectSettings {
+    /// Darken and redden the screen edges while the player is low on health.
+    pub vignette: bool,
+    /// Fraction of max health below which the vignette starts to show.
+    pub vignette_threshold: f32,
+    pub vignette_color: Color,
+    /// CRT scanlines, to go with the terminal font.
+    pub scanlines: bool,
+    /// How much darker the gaps between scanlines are, from 0 to 1.
+    pub scanline_strength: f32,
+    /// Height of one scanline in screen pixels.
+    pub scanline_spacing: f32,
+    /// Briefly freeze the game and flash the screen when the player is hit.
+    pub hit_stop: bool,
+    /// Split the color channels towards the screen edges when the player is hit or low on health.
+    pub chromatic_aberration: bool,
+    /// Largest distance between the color channels, in screen pixels.
+    pub aberration_strength: f32,
+    /// Real-time seconds the game stays frozen.
+    pub hit_stop_duration: f32,
+    /// Real-time seconds the flash takes to fade out.
+    pub flash_duration: f32,
+    pub flash_color: Color,
+}
+
+impl Default for ScreenEffectSettings {
+    fn default() -> Self {
+        Self {
+            vignette: true,
+            vignette_threshold: 0.35,
+            vignette_color: Color::srgb(0.45, 0.0, 0.0),
+            scanlines: true,
+            scanline_strength: 0.18,
+            scanline_spacing: 4.0,
+            hit_stop: true,
+            chromatic_aberration: true,
+            aberration_strength: 6.0,
+            hit_stop_duration: 0.06,
+            flash_duration: 0.15,
+            flash_color: Color::srgb(1.0, 0.9, 0.9),
+        }
+    }
+}
+
+/// Uniform for `assets/shaders/effects.wgsl`. Every field can be animated from gameplay systems;
+/// an effect at zero strength costs nothing beyond the shared pass.
+#[derive(Component, ShaderType, Clone, Copy, Default, PartialEq, Debug)]
+pub struct ScreenEffects {
+    pub vignette_color: Vec4,
+    pub flash_color: Vec4,
+    pub vignette: f32,
+    pub scanlines: f32,
+    pub scanline_spacing: f32,
+    pub flash: f32,
+    /// Channel offset at the screen edges, in pixels.
+    pub aberration: f32,
+    /// Seconds since startup, scrolls the scanlines.
+    pub time: f32,
+}
+
+impl ScreenEffects {
+    pub fn is_active(&self) -> bool {
+        self.vignette > 0.0 || self.scanlines > 0.0 || self.flash > 0.0 || self.aberration > 0.0
+    }
+}
+
+impl ExtractComponent for ScreenEffects {
+    type QueryData = &'static ScreenEffects;
+    type QueryFilter = ();
+    type Out = ScreenEffects;
+
+    fn extract_component(effects: QueryItem<'_, '_, Self::QueryData>) -> Option<Self::Out> {
+        effects.is_active().then_some(*effects)
+    }
+}
+
+impl FullscreenEffect for ScreenEffects {
+    const SHADER: &'static str = "shaders/effects.wgsl";
+    const LABEL: &'static str = "screen_effects";
+}
+
+/// Freezes virtual time for a moment after the player is hit. Ticks in real time so it can end
+/// while the game is paused.
+#[derive(Resource, Default)]
+struct HitStop {
+    freeze: Option<Timer>,
+    /// Fades out the flash and the chromatic aberration after a hit.
+    flash: Timer,
+}
+
+fn start_hit_stop(
+    mut damage_dealt: MessageReader<DamageDealt>,
+    settings: Res<ScreenEffectSettings>,
+    mut hit_stop: ResMut<HitStop>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+) {
+    // Status ticks chip away too often to freeze on
+    if !damage_dealt.read().any(|hit| hit.to_player && hit.status.is_none()) {
+        return;
+    }
+    hit_stop.flash = Timer::from_seconds(settings.flash_duration, TimerMode::Once);
+    if !settings.hit_stop {
+        return;
+    }
+    if hit_stop.freeze.is_none() {
+        virtual_time.pause();
+    }
+    hit_stop.freeze = Some(Timer::from_seconds(settings.hit_stop_duration, TimerMode::Once));
+}
+
+fn update_hit_stop(
+    real_time: Res<Time<Real>>,
+    paused: Res<Paused>,
+    mut hit_stop: ResMut<HitStop>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+) {
+    hit_stop.flash.tick(real_time.delta());
+    if let Some(freeze) = hit_stop.freeze.as_mut() {
+        if freeze.tick(real_time.delta()).is_finished() {
+            hit_stop.freeze = None;
+            // Pausing during the freeze keeps the game paused
+            if !paused.0 {
+                virtual_time.unpause();
+            }
+        }
+    }
+}
+
+/// Never leave the game frozen behind the game over screen or menu.
+fn end_hit_stop(mut hit_stop: ResMut<HitStop>, mut virtual_time: ResMut<Time<Virtual>>) {
+    if hit_stop.freeze.take().is_some() {
+        virtual_time.unpause();
+    }
+}
+
+fn animate_screen_effects(
+    real_time: Res<Time<Real>>,
+    settings: Res<ScreenEffectSettings>,
+    hit_stop: Res<HitStop>,
+    player_query: Query<&Health, With<Player>>,
+    mut query: Query<&mut ScreenEffects>,
+) {
+    let elapsed = real_time.elapsed_secs();
+
+    let danger = match player_query.single() {
+        Ok(health) if settings.vignette_threshold > 0.0 => (1.0 - health.current / health.max / settings.vignette_threshold).clamp(0.0, 1.0),
+        _ => 0.0,
+    };
+    // The vignette deepens as health drops and pulses like a heartbeat
+    let heartbeat = if danger > 0.0 { danger * (0.85 + 0.15 * (elapsed * (4.0 + 4.0 * danger)).sin()) } else { 0.0 };
+    let vignette = if settings.vignette { heartbeat } else { 0.0 };
+    let hit = if hit_stop.flash.is_finished() { 0.0 } else { 1.0 - hit_stop.flash.fraction() };
+    let flash = if settings.hit_stop { hit } else { 0.0 };
+    // Hits split the colors fully, low health keeps them slightly apart
+    let aberration = if settings.chromatic_aberration { settings.aberration_strength * hit.max(heartbeat * 0.4) } else { 0.0 };
+
+    let effects = ScreenEffects {
+        vignette_color: settings.vignette_color.to_linear().to_vec4(),
+        flash_color: settings.flash_color.to_linear().to_vec4(),
+        vignette,
+        scanlines: if settings.scanlines { settings.scanline_strength } else { 0.0 },
+        scanline_spacing: settings.scanline_spacing.max(1.0),
+        flash,
+        aberration,
+        time: elapsed,
+    };
+    for mut screen in query.iter_mut() {
+        screen.set_if_neq(effects);
+    }
+}
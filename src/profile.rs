@@ -0,0 +1,78 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::locale::Strings;
+use crate::storage;
+use crate::{GameState, RunStats, Score};
+
+// Profile Plugin
+pub struct ProfilePlugin;
+
+impl Plugin for ProfilePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Profile::load())
+            .add_systems(OnEnter(GameState::GameOver), record_run);
+    }
+}
+
+/// Progress that carries over between runs: meta-currency, purchases and personal bests.
+#[derive(Resource, Serialize, Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct Profile {
+    pub coins: u32,
+    /// Ids of characters bought with coins.
+    pub unlocked: Vec<String>,
+    pub runs: u32,
+    pub best_kills: u32,
+    pub best_survival_time: f32,
+}
+
+impl Profile {
+    const FILE: &'static str = "profile.ron";
+
+    pub fn load() -> Self {
+        storage::load(Self::FILE)
+    }
+
+    pub fn save(&self) {
+        storage::save(Self::FILE, self);
+    }
+}
+
+/// Milestones checked against the profile's personal bests.
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub enum Achievement {
+    /// Kill this many enemies in a single run.
+    Kills(u32),
+    /// Survive this many seconds in a single run.
+    Survive(f32),
+    /// Finish this many runs.
+    Runs(u32),
+}
+
+impl Achievement {
+    pub fn is_met(&self, profile: &Profile) -> bool {
+        match *self {
+            Achievement::Kills(kills) => profile.best_kills >= kills,
+            Achievement::Survive(secs) => profile.best_survival_time >= secs,
+            Achievement::Runs(runs) => profile.runs >= runs,
+        }
+    }
+
+    pub fn description(&self, strings: &Strings) -> String {
+        match *self {
+            Achievement::Kills(kills) => strings.plural("achievement.kills", kills, &[]),
+            Achievement::Survive(secs) => strings.format("achievement.survive", &[("time", &format!("{}:{:02}", secs as u32 / 60, secs as u32 % 60))]),
+            Achievement::Runs(runs) => strings.plural("achievement.runs", runs, &[]),
+        }
+    }
+}
+
+/// Pays out the run's score as coins and updates personal bests.
+fn record_run(score: Res<Score>, stats: Res<RunStats>, mut profile: ResMut<Profile>) {
+    profile.coins += score.0;
+    profile.runs += 1;
+    profile.best_kills = profile.best_kills.max(stats.kills);
+    profile.best_survival_time = profile.best_survival_time.max(stats.survival_time);
+    profile.save();
+}
@@ -0,0 +1,67 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::character::{Passive, Weapon};
+use crate::{Dead, GameState, Player};
+
+// Progression Plugin
+pub struct ProgressionPlugin;
+
+impl Plugin for ProgressionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Experience>()
+            .add_message::<LevelUp>()
+            .add_systems(Update, apply_level_ups.run_if(in_state(GameState::Playing)));
+    }
+}
+
+/// Experience gathered during the current run. Every kill is worth one point.
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Experience {
+    pub level: u32,
+    pub xp: u32,
+}
+
+impl Default for Experience {
+    fn default() -> Self {
+        Self { level: 1, xp: 0 }
+    }
+}
+
+impl Experience {
+    /// Experience needed to go from the current level to the next one.
+    pub fn xp_to_next(&self) -> u32 {
+        5 * self.level
+    }
+}
+
+#[derive(Message)]
+pub struct LevelUp {
+    pub level: u32,
+}
+
+/// Levels the player up while they have enough experience, alternating between upgrading
+/// the weapon (even levels) and the passive (odd levels).
+fn apply_level_ups(
+    mut experience: ResMut<Experience>,
+    mut level_ups: MessageWriter<LevelUp>,
+    mut player_query: Query<(&mut Weapon, &mut Passive), (With<Player>, Without<Dead>)>,
+) {
+    if !experience.is_changed() {
+        return;
+    }
+
+    while experience.xp >= experience.xp_to_next() {
+        experience.xp -= experience.xp_to_next();
+        experience.level += 1;
+        level_ups.write(LevelUp { level: experience.level });
+
+        if let Ok((mut weapon, mut passive)) = player_query.single_mut() {
+            if experience.level.is_multiple_of(2) {
+                weapon.level += 1;
+            } else {
+                passive.level += 1;
+            }
+        }
+    }
+}
@@ -0,0 +1,448 @@
+use bevy::prelude::*;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+
+use crate::character::{CharacterSelection, Passive, SelectedCharacter, Weapon};
+use crate::enemy::{EnemyArchetypes, EnemyKind, spawn_enemy};
+use crate::physics::{Frozen, Stunned, Velocity};
+use crate::progression::Experience;
+use crate::status::{StatusEffects, StatusKind};
+use crate::tileset::Tileset;
+use crate::{
+    Bullet, CollisionCooldown, Dead, ENEMY_BULLET_COLOR, Enemy, EnemyBullet, EnemySpawnTimer, EnemySpeed, GameState,
+    Health, LastDirection, Player, RunStats, Score, ShootingEnemy, bullet_sprite, spawn_player, storage,
+};
+
+// Save Plugin
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SavedRun { run: storage::load(FILE), resume: false })
+            .init_resource::<RunRng>()
+            .add_systems(OnExit(GameState::Playing), capture_run.pipe(store_run));
+    }
+}
+
+const FILE: &str = "run.ron";
+
+/// Randomness that decides how a run plays out, as opposed to cosmetic randomness. It is
+/// seeded from the run seed and saved along with the run, so a continued run rolls the same
+/// numbers it would have rolled had it never stopped.
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Default, Debug)]
+pub struct RunRng(u64);
+
+impl RunRng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+}
+
+impl RngCore for RunRng {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        // SplitMix64
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+}
+
+/// The run saved when the player last quit to the menu, offered as "Continue".
+#[derive(Resource)]
+pub struct SavedRun {
+    pub run: Option<RunSave>,
+    /// Set by the menu so that entering `Playing` continues the saved run instead of starting over.
+    pub resume: bool,
+}
+
+/// Everything needed to pick a run back up where it was left. Sprites, animations and health
+/// bars are rebuilt from the character and enemy data; the world and flow field are rebuilt
+/// from the seed and the player's position.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct RunSave {
+    character: usize,
+    player: PlayerSave,
+    enemies: Vec<EnemySave>,
+    bullets: Vec<BulletSave>,
+    score: u32,
+    stats: RunStats,
+    experience: Experience,
+    spawn_timer: Timer,
+    collision_cooldown: Timer,
+    rng: RunRng,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+struct PlayerSave {
+    position: Vec3,
+    health: Health,
+    direction: Vec3,
+    velocity: Vec2,
+    weapon_level: u32,
+    cooldown: Timer,
+    burst_remaining: u32,
+    burst_timer: Timer,
+    passive_level: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+struct EnemySave {
+    archetype: String,
+    position: Vec3,
+    health: Health,
+    speed: f32,
+    direction: Vec3,
+    velocity: Vec2,
+    shoot_timer: Option<Timer>,
+    effects: Option<StatusEffects>,
+    stunned: Option<Timer>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+struct BulletSave {
+    position: Vec3,
+    bullet: Bullet,
+    from_enemy: bool,
+}
+
+pub fn resuming(saved: Res<SavedRun>) -> bool {
+    saved.resume
+}
+
+/// Snapshots the run in progress. There is nothing to save once the player has died.
+fn capture_run(
+    selection: Res<CharacterSelection>,
+    player_query: Query<(&Transform, &Health, &LastDirection, &Velocity, &Weapon, &Passive), (With<Player>, Without<Dead>)>,
+    enemy_query: Query<
+        (&EnemyKind, &Transform, &Health, &EnemySpeed, &LastDirection, &Velocity, Option<&ShootingEnemy>, Option<&StatusEffects>, Option<&Stunned>),
+        (With<Enemy>, Without<Dead>),
+    >,
+    bullet_query: Query<(&Transform, &Bullet, Has<EnemyBullet>)>,
+    score: Res<Score>,
+    stats: Res<RunStats>,
+    experience: Res<Experience>,
+    spawn_timer: Res<EnemySpawnTimer>,
+    collision_cooldown: Res<CollisionCooldown>,
+    rng: Res<RunRng>,
+) -> Option<RunSave> {
+    let (transform, health, direction, velocity, weapon, passive) = player_query.single().ok()?;
+    if health.current <= 0.0 {
+        return None;
+    }
+
+    Some(RunSave {
+        character: selection.0,
+        player: PlayerSave {
+            position: transform.translation,
+            health: health.clone(),
+            direction: direction.0,
+            velocity: velocity.0,
+            weapon_level: weapon.level,
+            cooldown: weapon.cooldown.clone(),
+            burst_remaining: weapon.burst_remaining,
+            burst_timer: weapon.burst_timer.clone(),
+            passive_level: passive.level,
+        },
+        enemies: enemy_query
+            .iter()
+            .map(|(kind, transform, health, speed, direction, velocity, shooter, effects, stunned)| EnemySave {
+                archetype: kind.0.clone(),
+                position: transform.translation,
+                health: health.clone(),
+                speed: speed.0,
+                direction: direction.0,
+                velocity: velocity.0,
+                shoot_timer: shooter.map(|shooter| shooter.shoot_timer.clone()),
+                effects: effects.cloned(),
+                stunned: stunned.map(|stunned| stunned.0.clone()),
+            })
+            .collect(),
+        bullets: bullet_query
+            .iter()
+            .map(|(transform, bullet, from_enemy)| BulletSave { position: transform.translation, bullet: bullet.clone(), from_enemy })
+            .collect(),
+        score: score.0,
+        stats: stats.clone(),
+        experience: experience.clone(),
+        spawn_timer: spawn_timer.0.clone(),
+        collision_cooldown: collision_cooldown.0.clone(),
+        rng: rng.clone(),
+    })
+}
+
+/// Keeps the run for later when the player quits to the menu before dying.
+fn store_run(In(run): In<Option<RunSave>>, mut saved: ResMut<SavedRun>) {
+    if run.is_some() {
+        saved.run = run;
+        storage::save(FILE, &saved.run);
+    }
+}
+
+/// Starting a run uses up the saved one: it is continued if the player picked Continue and
+/// abandoned otherwise, so the same run can't be replayed from its save.
+pub fn take_saved_run(mut commands: Commands, mut saved: ResMut<SavedRun>, mut selection: ResMut<CharacterSelection>) {
+    let resume = std::mem::take(&mut saved.resume);
+    let Some(run) = saved.run.take() else {
+        return;
+    };
+    storage::remove(FILE);
+    if resume {
+        selection.0 = run.character;
+        commands.run_system_cached_with(restore_run, run);
+    }
+}
+
+/// Spawns a saved run back in.
+fn restore_run(
+    In(run): In<RunSave>,
+    mut commands: Commands,
+    tileset: Res<Tileset>,
+    character: SelectedCharacter,
+    archetypes: EnemyArchetypes,
+    mut score: ResMut<Score>,
+    mut stats: ResMut<RunStats>,
+    mut experience: ResMut<Experience>,
+    mut spawn_timer: ResMut<EnemySpawnTimer>,
+    mut collision_cooldown: ResMut<CollisionCooldown>,
+    mut rng: ResMut<RunRng>,
+) {
+    let character = character.get();
+    let mut weapon = Weapon::from(&character.weapon);
+    let bullet_color = weapon.color;
+    weapon.level = run.player.weapon_level;
+    weapon.cooldown = run.player.cooldown;
+    weapon.burst_remaining = run.player.burst_remaining;
+    weapon.burst_timer = run.player.burst_timer;
+    let mut passive = Passive::new(character.perk);
+    passive.level = run.player.passive_level;
+
+    let player = spawn_player(&mut commands, &tileset, &character);
+    commands.entity(player).insert((
+        Transform::from_translation(run.player.position).with_scale(Vec3::splat(4.0)),
+        run.player.health,
+        LastDirection(run.player.direction),
+        Velocity(run.player.velocity),
+        weapon,
+        passive,
+    ));
+
+    for enemy in run.enemies {
+        let Some(archetype) = archetypes.get(&enemy.archetype) else {
+            warn!("Saved enemy has unknown archetype {:?}", enemy.archetype);
+            continue;
+        };
+        // Speed is rolled on spawn, so roll it from throwaway randomness and put back the saved one
+        let entity = spawn_enemy(&mut commands, &tileset, &archetype, enemy.position.truncate(), &mut rand::rng());
+        let mut entity = commands.entity(entity);
+        entity.insert((
+            Transform::from_translation(enemy.position).with_scale(Vec3::splat(4.0)),
+            enemy.health,
+            EnemySpeed(enemy.speed),
+            LastDirection(enemy.direction),
+            Velocity(enemy.velocity),
+        ));
+        if let Some(shoot_timer) = enemy.shoot_timer {
+            entity.insert(ShootingEnemy { shoot_timer });
+        }
+        if let Some(effects) = enemy.effects {
+            // Frozen is otherwise only synced once the effects change, a frame too late
+            if effects.has(StatusKind::Freeze) {
+                entity.insert(Frozen);
+            }
+            entity.insert(effects);
+        }
+        if let Some(timer) = enemy.stunned {
+            entity.insert(Stunned(timer));
+        }
+    }
+
+    for bullet in run.bullets {
+        let color = if bullet.from_enemy { ENEMY_BULLET_COLOR } else { bullet_color };
+        let mut entity = commands.spawn((
+            bullet_sprite(color),
+            Transform::from_translation(bullet.position).with_scale(Vec3::splat(2.0)),
+            bullet.bullet,
+        ));
+        if bullet.from_enemy {
+            entity.insert(EnemyBullet);
+        }
+    }
+
+    score.0 = run.score;
+    *stats = run.stats;
+    *experience = run.experience;
+    spawn_timer.0 = run.spawn_timer;
+    collision_cooldown.0 = run.collision_cooldown;
+    *rng = run.rng;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+    use std::time::Duration;
+
+    use bevy::ecs::message::Messages;
+    use bevy::ecs::schedule::ExecutorKind;
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::state::app::StatesPlugin;
+
+    use super::*;
+    use crate::animation::AnimationEvent;
+    use crate::audio::PlaySound;
+    use crate::character::{CharacterRoster, CharacterRosterHandle};
+    use crate::enemy::{EnemyRoster, EnemyRosterHandle};
+    use crate::flowfield::FlowField;
+    use crate::input::ActionState;
+    use crate::physics::{Impulse, OnHit, PhysicsPlugin};
+    use crate::progression::ProgressionPlugin;
+    use crate::status::{StatusApplication, StatusPlugin};
+    use crate::world::WalkabilityGrid;
+    use crate::*;
+
+    const FRAME: Duration = Duration::from_nanos(16_666_667);
+
+    /// The gameplay of a run without rendering, input or assets, run in a fixed order.
+    fn headless_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((StatesPlugin, PhysicsPlugin, StatusPlugin, ProgressionPlugin))
+            .insert_state(GameState::Playing)
+            .add_message::<DamageEvent>()
+            .add_message::<DamageDealt>()
+            .add_message::<PlaySound>()
+            .add_message::<AnimationEvent>()
+            .init_resource::<Time>()
+            .init_resource::<ActionState>()
+            .init_resource::<Paused>()
+            .init_resource::<AimSettings>()
+            .init_resource::<FlowField>()
+            .init_resource::<WalkabilityGrid>()
+            .init_resource::<Score>()
+            .init_resource::<RunStats>()
+            .init_resource::<RunRng>()
+            .init_resource::<Tileset>()
+            .init_resource::<EnemyRosterHandle>()
+            .init_resource::<Assets<EnemyRoster>>()
+            .init_resource::<CharacterRosterHandle>()
+            .init_resource::<Assets<CharacterRoster>>()
+            .init_resource::<CharacterSelection>()
+            .insert_resource(CollisionCooldown(Timer::from_seconds(0.5, TimerMode::Repeating)))
+            .insert_resource(EnemySpawnTimer(Timer::from_seconds(1.0, TimerMode::Repeating)))
+            .configure_sets(Update, (DamageSystems::Deal, DamageSystems::Apply, DamageSystems::React).chain())
+            .add_systems(
+                Update,
+                (
+                    (move_player, move_enemies, shoot_bullet, enemy_shoot_bullets, move_bullets, check_bullet_collisions, check_collisions).chain().in_set(DamageSystems::Deal),
+                    apply_damage.in_set(DamageSystems::Apply),
+                    (spawn_enemies, track_survival_time).chain().after(DamageSystems::React),
+                ),
+            )
+            .edit_schedule(Update, |schedule| {
+                schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+            });
+        app
+    }
+
+    /// A run with enemies closing in from every side and rings of bullets that knock back, stun
+    /// and inflict every kind of status effect some of the time.
+    fn start_run(mut commands: Commands, tileset: Res<Tileset>, character: SelectedCharacter, archetypes: EnemyArchetypes, mut rng: ResMut<RunRng>) {
+        *rng = RunRng::new(7);
+        spawn_player(&mut commands, &tileset, &character.get());
+
+        for (index, id) in ["snake", "demon", "shooter"].into_iter().cycle().take(12).enumerate() {
+            let archetype = archetypes.get(id).unwrap();
+            let position = Vec2::from_angle(index as f32 * TAU / 12.0) * 300.0;
+            spawn_enemy(&mut commands, &tileset, &archetype, position, rng.as_mut());
+        }
+
+        let status = |kind, duration, magnitude| StatusApplication { kind, duration, magnitude, chance: 0.5 };
+        let on_hit = OnHit {
+            knockback: 150.0,
+            stun: 0.2,
+            status: vec![
+                status(StatusKind::Burn, 2.0, 8.0),
+                status(StatusKind::Poison, 2.0, 3.0),
+                status(StatusKind::Slow, 1.0, 0.5),
+                status(StatusKind::Freeze, 0.3, 0.0),
+                status(StatusKind::Bleed, 1.5, 4.0),
+            ],
+        };
+        for (ring, start) in [20.0, -150.0, -300.0].into_iter().enumerate() {
+            for index in 0..24 {
+                let direction = Vec2::from_angle((index as f32 + ring as f32 / 3.0) * TAU / 24.0).extend(0.0);
+                commands.spawn((
+                    Transform::from_translation(direction * start),
+                    Bullet { velocity: direction * 300.0, damage: 12.0, crit: index % 5 == 0, on_hit: on_hit.clone() },
+                ));
+            }
+        }
+    }
+
+    /// Snapshot of the run with entities sorted by position, since a restored world stores them
+    /// in a different order.
+    fn snapshot(app: &mut App) -> Option<RunSave> {
+        let mut run = app.world_mut().run_system_once(capture_run).unwrap()?;
+        let by_position = |a: Vec3, b: Vec3| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y));
+        run.enemies.sort_by(|a, b| by_position(a.position, b.position));
+        run.bullets.sort_by(|a, b| by_position(a.position, b.position));
+        Some(run)
+    }
+
+    fn step(app: &mut App) -> Option<RunSave> {
+        app.world_mut().resource_mut::<Time>().advance_by(FRAME);
+        app.update();
+        snapshot(app)
+    }
+
+    /// Whether the last frame sent hits, knockback and status damage, which a save must not lose.
+    fn mid_hit(app: &App) -> bool {
+        fn sent<M: Message>(app: &App) -> bool {
+            app.world().resource::<Messages<M>>().iter_current_update_messages().next().is_some()
+        }
+        sent::<DamageEvent>(app) && sent::<DamageDealt>(app) && sent::<Impulse>(app)
+    }
+
+    #[test]
+    fn continued_run_plays_out_like_the_original() {
+        let mut original = headless_app();
+        original.world_mut().run_system_once(start_run).unwrap();
+
+        // Quitting can happen on any frame, so save on one in the thick of the fighting
+        let mut frames = 0;
+        while frames < 45 || !mid_hit(&original) {
+            step(&mut original);
+            frames += 1;
+            assert!(frames < 300, "nothing was hit");
+        }
+        let save = original.world_mut().run_system_once(capture_run).unwrap().expect("the player is alive");
+        assert!(save.enemies.iter().any(|enemy| enemy.effects.is_some()), "no status effects to save");
+        assert!(!save.bullets.is_empty(), "no bullets to save");
+
+        let text = ron::ser::to_string_pretty(&save, ron::ser::PrettyConfig::default()).unwrap();
+        let loaded: RunSave = ron::from_str(&text).unwrap();
+        assert_eq!(loaded, save);
+
+        let mut restored = headless_app();
+        restored.world_mut().run_system_once_with(restore_run, loaded).unwrap();
+        assert_eq!(snapshot(&mut restored), snapshot(&mut original));
+
+        for frame in 0..120 {
+            let expected = step(&mut original);
+            let actual = step(&mut restored);
+            assert!(expected.is_some(), "the player died on frame {frame}");
+            assert_eq!(actual, expected, "frame {frame}");
+        }
+    }
+}
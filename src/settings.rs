@@ -0,0 +1,408 @@
+use bevy::prelude::*;
+use bevy::window::{MonitorSelection, PresentMode, PrimaryWindow, WindowMode};
+use serde::{Deserialize, Serialize};
+
+use crate::accessibility::{AccessibilitySettings, Palette};
+use crate::audio::VolumeSettings;
+use crate::camera::CameraSettings;
+use crate::feedback::FeedbackSettings;
+use crate::input::{Action, ActionState, Bindings};
+use crate::postprocess::ScreenEffectSettings;
+use crate::locale::{LANGUAGES, Locale, Strings};
+use crate::widgets::{self, ButtonPressed, ButtonStepped, RowValue};
+use crate::{AimSettings, GameState, storage};
+
+// Settings Plugin
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, apply_settings)
+            .add_systems(OnEnter(GameState::Settings), setup_settings)
+            .add_systems(Update, (settings_input, update_settings_text).chain().run_if(in_state(GameState::Settings)))
+            .add_systems(OnExit(GameState::Settings), cleanup_settings);
+    }
+}
+
+/// Window sizes offered in windowed mode.
+const RESOLUTIONS: [(u32, u32); 5] = [(960, 540), (1280, 720), (1600, 900), (1920, 1080), (2560, 1440)];
+
+/// Player preferences and key bindings, saved to `settings.ron` in the config directory.
+/// Fields missing from the file keep their defaults.
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct Settings {
+    /// Code of the language UI text is shown in, one of `LANGUAGES`.
+    pub language: String,
+    pub fullscreen: bool,
+    pub vsync: bool,
+    /// Window size in windowed mode.
+    pub resolution: (u32, u32),
+    pub ui_scale: f32,
+    /// Multiplier on screen shake; 0 turns it off.
+    pub shake_intensity: f32,
+    pub damage_numbers: bool,
+    /// Reddens the screen edges at low health.
+    pub vignette: bool,
+    pub scanlines: bool,
+    /// Freezes the game and flashes the screen for a moment when the player is hit.
+    pub hit_stop: bool,
+    pub chromatic_aberration: bool,
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub auto_aim: bool,
+    /// Blends pixels when sprites are scaled instead of keeping them sharp. Only read at startup.
+    pub smooth_textures: bool,
+    /// Colors for health bars and bullets.
+    pub palette: Palette,
+    /// Turns off screen shake and the death animation's melting and spiral.
+    pub reduced_motion: bool,
+    /// Outlines the game world and turns off scanlines.
+    pub high_contrast: bool,
+    /// How fast the game runs, from half speed to full speed.
+    pub game_speed: f32,
+    /// Edited on the controls screen.
+    pub bindings: Bindings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        let volume = VolumeSettings::default();
+        Self {
+            language: LANGUAGES[0].to_string(),
+            fullscreen: false,
+            vsync: true,
+            resolution: (1280, 720),
+            ui_scale: 1.0,
+            shake_intensity: 1.0,
+            damage_numbers: true,
+            vignette: true,
+            scanlines: true,
+            hit_stop: true,
+            chromatic_aberration: true,
+            master_volume: volume.master,
+            music_volume: volume.music,
+            sfx_volume: volume.sfx,
+            auto_aim: false,
+            smooth_textures: false,
+            palette: Palette::Standard,
+            reduced_motion: false,
+            high_contrast: false,
+            game_speed: 1.0,
+            bindings: Bindings::default(),
+        }
+    }
+}
+
+impl Settings {
+    const FILE: &'static str = "settings.ron";
+
+    pub fn load() -> Self {
+        let mut settings: Settings = storage::load_config(Self::FILE);
+        settings.bindings.fill_missing();
+        settings
+    }
+
+    pub fn save(&self) {
+        storage::save_config(Self::FILE, self);
+    }
+
+    pub fn image_plugin(&self) -> ImagePlugin {
+        if self.smooth_textures { ImagePlugin::default_linear() } else { ImagePlugin::default_nearest() }
+    }
+
+    /// The primary window as these settings describe it.
+    pub fn window(&self) -> Window {
+        let mut window = Window { title: "Gragusi Survivors".to_string(), ..default() };
+        self.apply_to_window(&mut window);
+        window
+    }
+
+    fn apply_to_window(&self, window: &mut Window) {
+        window.present_mode = if self.vsync { PresentMode::AutoVsync } else { PresentMode::AutoNoVsync };
+        window.mode = if self.fullscreen { WindowMode::BorderlessFullscreen(MonitorSelection::Current) } else { WindowMode::Windowed };
+        if !self.fullscreen {
+            window.resolution.set(self.resolution.0 as f32, self.resolution.1 as f32);
+        }
+    }
+}
+
+/// Pushes changed settings out to the window and to each plugin's own settings.
+fn apply_settings(
+    settings: Res<Settings>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+    mut ui_scale: ResMut<UiScale>,
+    mut camera: ResMut<CameraSettings>,
+    mut feedback: ResMut<FeedbackSettings>,
+    mut volume: ResMut<VolumeSettings>,
+    mut aim: ResMut<AimSettings>,
+    mut accessibility: ResMut<AccessibilitySettings>,
+    mut screen_effects: ResMut<ScreenEffectSettings>,
+    mut bindings: ResMut<Bindings>,
+    mut locale: ResMut<Locale>,
+    mut applied: Local<Option<Settings>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    // Leave the window alone unless its own settings changed, so resizing it by hand sticks
+    let window_changed = applied
+        .as_ref()
+        .is_none_or(|applied| (applied.fullscreen, applied.vsync, applied.resolution) != (settings.fullscreen, settings.vsync, settings.resolution));
+    if let (true, Ok(mut window)) = (window_changed, window_query.single_mut()) {
+        settings.apply_to_window(&mut window);
+    }
+    *applied = Some(settings.clone());
+    ui_scale.0 = settings.ui_scale;
+    camera.shake_intensity = if settings.reduced_motion { 0.0 } else { settings.shake_intensity };
+    feedback.damage_numbers = settings.damage_numbers;
+    *volume = VolumeSettings { master: settings.master_volume, music: settings.music_volume, sfx: settings.sfx_volume };
+    aim.auto_aim = settings.auto_aim;
+    *accessibility = AccessibilitySettings {
+        palette: settings.palette,
+        reduced_motion: settings.reduced_motion,
+        high_contrast: settings.high_contrast,
+        game_speed: settings.game_speed,
+    };
+    screen_effects.vignette = settings.vignette;
+    screen_effects.scanlines = settings.scanlines && !settings.high_contrast;
+    screen_effects.hit_stop = settings.hit_stop;
+    screen_effects.chromatic_aberration = settings.chromatic_aberration;
+    bindings.set_if_neq(settings.bindings.clone());
+    // Only touch the locale on a real change, since that redraws all text
+    if locale.language != settings.language {
+        locale.language = settings.language.clone();
+    }
+}
+
+/// A button on the settings screen. Left and right change its value; Confirm toggles it.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+enum SettingsRow {
+    Language,
+    Fullscreen,
+    Vsync,
+    Resolution,
+    UiScale,
+    ScreenShake,
+    DamageNumbers,
+    Vignette,
+    Scanlines,
+    HitStop,
+    ChromaticAberration,
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    AutoAim,
+    SmoothTextures,
+    Palette,
+    ReducedMotion,
+    HighContrast,
+    GameSpeed,
+    Controls,
+    Reset,
+    Done,
+}
+
+impl SettingsRow {
+    const ALL: [SettingsRow; 23] = [
+        SettingsRow::Language,
+        SettingsRow::Fullscreen,
+        SettingsRow::Vsync,
+        SettingsRow::Resolution,
+        SettingsRow::UiScale,
+        SettingsRow::ScreenShake,
+        SettingsRow::DamageNumbers,
+        SettingsRow::Vignette,
+        SettingsRow::Scanlines,
+        SettingsRow::HitStop,
+        SettingsRow::ChromaticAberration,
+        SettingsRow::MasterVolume,
+        SettingsRow::MusicVolume,
+        SettingsRow::SfxVolume,
+        SettingsRow::AutoAim,
+        SettingsRow::SmoothTextures,
+        SettingsRow::Palette,
+        SettingsRow::ReducedMotion,
+        SettingsRow::HighContrast,
+        SettingsRow::GameSpeed,
+        SettingsRow::Controls,
+        SettingsRow::Reset,
+        SettingsRow::Done,
+    ];
+
+    /// Key of the row's name in the string tables.
+    fn name(self) -> &'static str {
+        match self {
+            SettingsRow::Language => "settings.language",
+            SettingsRow::Fullscreen => "settings.fullscreen",
+            SettingsRow::Vsync => "settings.vsync",
+            SettingsRow::Resolution => "settings.resolution",
+            SettingsRow::UiScale => "settings.ui_scale",
+            SettingsRow::ScreenShake => "settings.screen_shake",
+            SettingsRow::DamageNumbers => "settings.damage_numbers",
+            SettingsRow::Vignette => "settings.vignette",
+            SettingsRow::Scanlines => "settings.scanlines",
+            SettingsRow::HitStop => "settings.hit_stop",
+            SettingsRow::ChromaticAberration => "settings.chromatic_aberration",
+            SettingsRow::MasterVolume => "settings.master_volume",
+            SettingsRow::MusicVolume => "settings.music_volume",
+            SettingsRow::SfxVolume => "settings.sfx_volume",
+            SettingsRow::AutoAim => "settings.auto_aim",
+            SettingsRow::SmoothTextures => "settings.smooth_textures",
+            SettingsRow::Palette => "settings.palette",
+            SettingsRow::ReducedMotion => "settings.reduced_motion",
+            SettingsRow::HighContrast => "settings.high_contrast",
+            SettingsRow::GameSpeed => "settings.game_speed",
+            SettingsRow::Controls => "settings.controls",
+            SettingsRow::Reset => "common.reset",
+            SettingsRow::Done => "common.done",
+        }
+    }
+
+    fn value(self, settings: &Settings, strings: &Strings) -> Option<String> {
+        let on_off = |on: bool| strings.get(if on { "common.on" } else { "common.off" });
+        let percent = |value: f32| format!("{:.0}%", value * 100.0);
+        Some(match self {
+            SettingsRow::Language => strings.language_name(&settings.language),
+            SettingsRow::Fullscreen => on_off(settings.fullscreen),
+            SettingsRow::Vsync => on_off(settings.vsync),
+            SettingsRow::Resolution => format!("{}x{}", settings.resolution.0, settings.resolution.1),
+            SettingsRow::UiScale => format!("{:.2}x", settings.ui_scale),
+            SettingsRow::ScreenShake => percent(settings.shake_intensity),
+            SettingsRow::DamageNumbers => on_off(settings.damage_numbers),
+            SettingsRow::Vignette => on_off(settings.vignette),
+            SettingsRow::Scanlines => on_off(settings.scanlines),
+            SettingsRow::HitStop => on_off(settings.hit_stop),
+            SettingsRow::ChromaticAberration => on_off(settings.chromatic_aberration),
+            SettingsRow::MasterVolume => percent(settings.master_volume),
+            SettingsRow::MusicVolume => percent(settings.music_volume),
+            SettingsRow::SfxVolume => percent(settings.sfx_volume),
+            SettingsRow::AutoAim => on_off(settings.auto_aim),
+            SettingsRow::SmoothTextures => strings.format("settings.after_restart", &[("value", &on_off(settings.smooth_textures))]),
+            SettingsRow::Palette => strings.get(settings.palette.name()),
+            SettingsRow::ReducedMotion => on_off(settings.reduced_motion),
+            SettingsRow::HighContrast => on_off(settings.high_contrast),
+            SettingsRow::GameSpeed => percent(settings.game_speed),
+            SettingsRow::Controls | SettingsRow::Reset | SettingsRow::Done => return None,
+        })
+    }
+
+    /// Steps the row's value up (`step` 1) or down (`step` -1).
+    fn adjust(self, settings: &mut Settings, step: i32) {
+        let nudge = |value: &mut f32, by: f32, min: f32, max: f32| *value = (*value + by * step as f32).clamp(min, max);
+        match self {
+            SettingsRow::Language => {
+                let current = LANGUAGES.iter().position(|code| *code == settings.language).unwrap_or(0);
+                let next = (current as i32 + step).rem_euclid(LANGUAGES.len() as i32);
+                settings.language = LANGUAGES[next as usize].to_string();
+            }
+            SettingsRow::Fullscreen => settings.fullscreen = !settings.fullscreen,
+            SettingsRow::Vsync => settings.vsync = !settings.vsync,
+            SettingsRow::Resolution => {
+                let current = RESOLUTIONS.iter().position(|resolution| *resolution == settings.resolution).unwrap_or(1);
+                let next = (current as i32 + step).rem_euclid(RESOLUTIONS.len() as i32);
+                settings.resolution = RESOLUTIONS[next as usize];
+            }
+            SettingsRow::UiScale => nudge(&mut settings.ui_scale, 0.25, 0.5, 2.0),
+            SettingsRow::ScreenShake => nudge(&mut settings.shake_intensity, 0.25, 0.0, 2.0),
+            SettingsRow::DamageNumbers => settings.damage_numbers = !settings.damage_numbers,
+            SettingsRow::Vignette => settings.vignette = !settings.vignette,
+            SettingsRow::Scanlines => settings.scanlines = !settings.scanlines,
+            SettingsRow::HitStop => settings.hit_stop = !settings.hit_stop,
+            SettingsRow::ChromaticAberration => settings.chromatic_aberration = !settings.chromatic_aberration,
+            SettingsRow::MasterVolume => nudge(&mut settings.master_volume, 0.1, 0.0, 1.0),
+            SettingsRow::MusicVolume => nudge(&mut settings.music_volume, 0.1, 0.0, 1.0),
+            SettingsRow::SfxVolume => nudge(&mut settings.sfx_volume, 0.1, 0.0, 1.0),
+            SettingsRow::AutoAim => settings.auto_aim = !settings.auto_aim,
+            SettingsRow::SmoothTextures => settings.smooth_textures = !settings.smooth_textures,
+            SettingsRow::Palette => {
+                let current = Palette::ALL.iter().position(|palette| *palette == settings.palette).unwrap_or(0);
+                let next = (current as i32 + step).rem_euclid(Palette::ALL.len() as i32);
+                settings.palette = Palette::ALL[next as usize];
+            }
+            SettingsRow::ReducedMotion => settings.reduced_motion = !settings.reduced_motion,
+            SettingsRow::HighContrast => settings.high_contrast = !settings.high_contrast,
+            SettingsRow::GameSpeed => nudge(&mut settings.game_speed, 0.1, 0.5, 1.0),
+            SettingsRow::Controls | SettingsRow::Reset | SettingsRow::Done => {}
+        }
+    }
+}
+
+#[derive(Component)]
+struct SettingsUI;
+
+fn setup_settings(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load(widgets::FONT);
+    commands.spawn((widgets::screen(), SettingsUI)).with_children(|parent| {
+        parent.spawn(widgets::title(&font, "settings.title"));
+        parent.spawn(widgets::list(Val::Percent(65.0))).with_children(|list| {
+            for row in SettingsRow::ALL {
+                match row {
+                    SettingsRow::Controls | SettingsRow::Reset | SettingsRow::Done => list.spawn((widgets::button(&font, row.name()), row)),
+                    _ => list.spawn((widgets::stepper(&font, row.name()), row)),
+                };
+            }
+        });
+        parent.spawn(widgets::hint(&font, "settings.hint"));
+    });
+}
+
+fn settings_input(
+    actions: Res<ActionState>,
+    mut pressed: MessageReader<ButtonPressed>,
+    mut stepped: MessageReader<ButtonStepped>,
+    row_query: Query<&SettingsRow>,
+    mut settings: ResMut<Settings>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if actions.just_pressed(Action::Back) {
+        next_state.set(GameState::Menu);
+        return;
+    }
+    for ButtonStepped { entity, step } in stepped.read() {
+        if let Ok(row) = row_query.get(*entity) {
+            row.adjust(&mut settings, *step);
+        }
+    }
+    for ButtonPressed(entity) in pressed.read() {
+        let Ok(&row) = row_query.get(*entity) else {
+            continue;
+        };
+        match row {
+            SettingsRow::Controls => next_state.set(GameState::Controls),
+            // The controls screen resets the bindings on its own
+            SettingsRow::Reset => *settings = Settings { bindings: settings.bindings.clone(), ..default() },
+            SettingsRow::Done => next_state.set(GameState::Menu),
+            _ => row.adjust(&mut settings, 1),
+        }
+    }
+}
+
+fn update_settings_text(
+    settings: Res<Settings>,
+    strings: Strings,
+    row_query: Query<(Ref<SettingsRow>, &Children)>,
+    mut text_query: Query<&mut Text, With<RowValue>>,
+) {
+    for (row, children) in row_query.iter() {
+        if !settings.is_changed() && !strings.is_changed() && !row.is_added() {
+            continue;
+        }
+        let Some(value) = row.value(&settings, &strings) else {
+            continue;
+        };
+        let mut texts = text_query.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.0.clone_from(&value);
+        }
+    }
+}
+
+/// Saves the settings on the way out.
+fn cleanup_settings(mut commands: Commands, settings: Res<Settings>, query: Query<Entity, With<SettingsUI>>) {
+    settings.save();
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
@@ -0,0 +1,137 @@
+use bevy::ecs::query::QueryItem;
+use bevy::prelude::*;
+use bevy::render::extract_component::ExtractComponent;
+use bevy::render::render_resource::ShaderType;
+
+use crate::DeathTransition;
+use crate::accessibility::AccessibilitySettings;
+use crate::postprocess::{FullscreenEffect, FullscreenEffectPlugin};
+
+// Spiral Plugin
+pub struct SpiralPlugin;
+
+impl Plugin for SpiralPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(FullscreenEffectPlugin::<SpiralSettings>::default())
+            .add_systems(Update, drive_spiral);
+    }
+}
+
+/// How far the screen has twisted and darkened by the end of the death transition.
+const SPIRAL_TIME: f32 = 3.0;
+const SPIRAL_INTENSITY: f32 = 6.0;
+
+/// Twists the camera's view around the center of the screen and darkens it from the edges in.
+/// Matches the `SpiralSettings` uniform in `assets/shaders/spiral.wgsl`.
+#[derive(Component, ShaderType, Clone, Copy, Default, PartialEq, Debug)]
+pub struct SpiralSettings {
+    pub time: f32,
+    pub intensity: f32,
+}
+
+impl SpiralSettings {
+    /// Settings for a death transition that is `progress` (0 to 1) of the way through. Eased in so
+    /// the first moments after dying still show the explosion clearly.
+    pub fn for_transition(progress: f32) -> Self {
+        let eased = progress.clamp(0.0, 1.0).powi(2);
+        Self { time: eased * SPIRAL_TIME, intensity: eased * SPIRAL_INTENSITY }
+    }
+
+    /// The shader leaves the image untouched at zero time or intensity, so the pass is skipped.
+    pub fn is_active(&self) -> bool {
+        self.time > 0.0 && self.intensity > 0.0
+    }
+}
+
+impl FullscreenEffect for SpiralSettings {
+    const SHADER: &'static str = "shaders/spiral.wgsl";
+    const LABEL: &'static str = "spiral";
+}
+
+impl ExtractComponent for SpiralSettings {
+    type QueryData = &'static SpiralSettings;
+    type QueryFilter = ();
+    type Out = SpiralSettings;
+
+    fn extract_component(settings: QueryItem<'_, '_, Self::QueryData>) -> Option<Self::Out> {
+        settings.is_active().then_some(*settings)
+    }
+}
+
+/// Follows the death transition. The transition is reset when a run starts and when returning to
+/// the menu, which straightens the screen out again. Stays still with reduced motion.
+fn drive_spiral(transition: Res<DeathTransition>, accessibility: Res<AccessibilitySettings>, mut query: Query<&mut SpiralSettings>) {
+    let progress = if accessibility.reduced_motion { 0.0 } else { transition.timer.fraction() };
+    let settings = SpiralSettings::for_transition(progress);
+    for mut spiral in query.iter_mut() {
+        spiral.set_if_neq(settings);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    #[test]
+    fn inactive_settings_are_not_extracted() {
+        assert_eq!(SpiralSettings::extract_component(&SpiralSettings::default()), None);
+        assert_eq!(SpiralSettings::extract_component(&SpiralSettings { time: 1.0, intensity: 0.0 }), None);
+        assert_eq!(SpiralSettings::extract_component(&SpiralSettings { time: 0.0, intensity: 1.0 }), None);
+    }
+
+    #[test]
+    fn active_settings_are_extracted_unchanged() {
+        let settings = SpiralSettings { time: 1.5, intensity: 2.0 };
+        assert_eq!(SpiralSettings::extract_component(&settings), Some(settings));
+    }
+
+    #[test]
+    fn transition_ramps_up_from_nothing() {
+        assert!(!SpiralSettings::for_transition(0.0).is_active());
+        assert_eq!(SpiralSettings::for_transition(1.0), SpiralSettings { time: SPIRAL_TIME, intensity: SPIRAL_INTENSITY });
+        assert_eq!(SpiralSettings::for_transition(2.0), SpiralSettings::for_transition(1.0));
+
+        let halfway = SpiralSettings::for_transition(0.5);
+        let later = SpiralSettings::for_transition(0.75);
+        assert!(halfway.is_active());
+        assert!(later.time > halfway.time && later.intensity > halfway.intensity);
+    }
+
+    #[test]
+    fn camera_follows_the_death_transition() {
+        let mut world = World::new();
+        let mut transition = DeathTransition::default();
+        transition.timer.tick(Duration::from_secs(1));
+        let expected = SpiralSettings::for_transition(transition.timer.fraction());
+        world.insert_resource(transition);
+        world.init_resource::<AccessibilitySettings>();
+        let camera = world.spawn(SpiralSettings::default()).id();
+
+        world.run_system_once(drive_spiral).unwrap();
+        let settings = *world.get::<SpiralSettings>(camera).unwrap();
+        assert_eq!(settings, expected);
+        assert_eq!(SpiralSettings::extract_component(&settings), Some(expected));
+
+        world.insert_resource(DeathTransition::default());
+        world.run_system_once(drive_spiral).unwrap();
+        let settings = *world.get::<SpiralSettings>(camera).unwrap();
+        assert_eq!(SpiralSettings::extract_component(&settings), None);
+    }
+
+    #[test]
+    fn reduced_motion_keeps_the_screen_still() {
+        let mut world = World::new();
+        let mut transition = DeathTransition::default();
+        transition.timer.tick(Duration::from_secs(1));
+        world.insert_resource(transition);
+        world.insert_resource(AccessibilitySettings { reduced_motion: true, ..default() });
+        let camera = world.spawn(SpiralSettings::default()).id();
+
+        world.run_system_once(drive_spiral).unwrap();
+        assert!(!world.get::<SpiralSettings>(camera).unwrap().is_active());
+    }
+}
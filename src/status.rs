@@ -0,0 +1,286 @@
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::feedback::Tint;
+use crate::physics::{Frozen, OnHit};
+use crate::save::RunRng;
+use crate::{DamageDealt, DamageEvent, DamageSystems, GameState};
+
+// Status Plugin
+pub struct StatusPlugin;
+
+impl Plugin for StatusPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (tick_statuses.in_set(DamageSystems::Deal), (apply_statuses, sync_status_visuals).chain().in_set(DamageSystems::React))
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// Seconds between damage ticks of burn, poison and bleed.
+const TICK_INTERVAL: f32 = 0.5;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StatusKind {
+    /// Damage over time. Reapplying refreshes the duration and keeps the stronger burn.
+    Burn,
+    /// Damage over time that stacks in intensity up to five times.
+    Poison,
+    /// Reduces movement speed by `magnitude` (a fraction). The strongest slow wins.
+    Slow,
+    /// Stops the target completely and makes it ignore knockback.
+    Freeze,
+    /// Every application is its own stack with its own duration, up to ten.
+    Bleed,
+}
+
+enum Stacking {
+    /// One instance; reapplying refreshes the duration and keeps the larger magnitude.
+    Refresh,
+    /// One instance whose stack count goes up on every application.
+    Intensity { max: u32 },
+    /// Separate instances that each run out on their own.
+    Independent { max: usize },
+}
+
+impl StatusKind {
+    fn stacking(self) -> Stacking {
+        match self {
+            StatusKind::Burn | StatusKind::Slow | StatusKind::Freeze => Stacking::Refresh,
+            StatusKind::Poison => Stacking::Intensity { max: 5 },
+            StatusKind::Bleed => Stacking::Independent { max: 10 },
+        }
+    }
+
+    fn deals_damage(self) -> bool {
+        matches!(self, StatusKind::Burn | StatusKind::Poison | StatusKind::Bleed)
+    }
+
+    pub fn tint(self) -> Color {
+        match self {
+            StatusKind::Burn => Color::srgb(1.0, 0.55, 0.3),
+            StatusKind::Poison => Color::srgb(0.55, 1.0, 0.45),
+            StatusKind::Slow => Color::srgb(0.75, 0.7, 0.9),
+            StatusKind::Freeze => Color::srgb(0.45, 0.75, 1.0),
+            StatusKind::Bleed => Color::srgb(0.9, 0.3, 0.35),
+        }
+    }
+
+    /// When several effects are active, the sprite takes the tint of the highest one.
+    fn tint_priority(self) -> u8 {
+        match self {
+            StatusKind::Freeze => 4,
+            StatusKind::Burn => 3,
+            StatusKind::Poison => 2,
+            StatusKind::Bleed => 1,
+            StatusKind::Slow => 0,
+        }
+    }
+}
+
+/// A status effect carried by a hit, as written in the data files.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct StatusApplication {
+    pub kind: StatusKind,
+    /// Seconds the effect lasts.
+    pub duration: f32,
+    /// Damage per second per stack for burn, poison and bleed, or the speed fraction removed by slow.
+    #[serde(default)]
+    pub magnitude: f32,
+    /// Chance for the hit to apply the effect at all.
+    #[serde(default = "default_chance")]
+    pub chance: f32,
+}
+
+fn default_chance() -> f32 {
+    1.0
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+struct ActiveStatus {
+    kind: StatusKind,
+    remaining: f32,
+    magnitude: f32,
+    stacks: u32,
+}
+
+/// Status effects currently affecting an entity.
+#[derive(Component, Serialize, Deserialize, Clone, PartialEq, Default, Debug)]
+pub struct StatusEffects {
+    active: Vec<ActiveStatus>,
+    tick: f32,
+}
+
+impl StatusEffects {
+    fn apply(&mut self, application: &StatusApplication) {
+        let existing = self.active.iter().position(|status| status.kind == application.kind);
+
+        match (application.kind.stacking(), existing) {
+            (Stacking::Refresh, Some(index)) => {
+                let status = &mut self.active[index];
+                status.remaining = status.remaining.max(application.duration);
+                status.magnitude = status.magnitude.max(application.magnitude);
+            }
+            (Stacking::Intensity { max }, Some(index)) => {
+                let status = &mut self.active[index];
+                status.remaining = application.duration;
+                status.magnitude = status.magnitude.max(application.magnitude);
+                status.stacks = (status.stacks + 1).min(max);
+            }
+            (Stacking::Independent { max }, _) => {
+                let count = self.active.iter().filter(|status| status.kind == application.kind).count();
+                if count >= max {
+                    // Replace the stack closest to running out
+                    if let Some(oldest) = self
+                        .active
+                        .iter_mut()
+                        .filter(|status| status.kind == application.kind)
+                        .min_by(|a, b| a.remaining.total_cmp(&b.remaining))
+                    {
+                        oldest.remaining = application.duration;
+                        oldest.magnitude = application.magnitude;
+                    }
+                } else {
+                    self.active.push(ActiveStatus::new(application));
+                }
+            }
+            (_, None) => self.active.push(ActiveStatus::new(application)),
+        }
+    }
+
+    pub fn has(&self, kind: StatusKind) -> bool {
+        self.active.iter().any(|status| status.kind == kind)
+    }
+
+    /// Multiplier for an entity's own movement speed.
+    pub fn speed_multiplier(&self) -> f32 {
+        if self.has(StatusKind::Freeze) {
+            return 0.0;
+        }
+        let slow = self
+            .active
+            .iter()
+            .filter(|status| status.kind == StatusKind::Slow)
+            .map(|status| status.magnitude)
+            .fold(0.0, f32::max);
+        (1.0 - slow).clamp(0.0, 1.0)
+    }
+
+    fn tint(&self) -> Option<Color> {
+        self.active
+            .iter()
+            .max_by_key(|status| status.kind.tint_priority())
+            .map(|status| status.kind.tint())
+    }
+}
+
+impl ActiveStatus {
+    fn new(application: &StatusApplication) -> Self {
+        Self {
+            kind: application.kind,
+            remaining: application.duration,
+            magnitude: application.magnitude,
+            stacks: 1,
+        }
+    }
+}
+
+/// Status effects an enemy archetype shrugs off.
+#[derive(Component)]
+pub struct StatusImmunity(pub Vec<StatusKind>);
+
+fn apply_statuses(
+    mut commands: Commands,
+    mut damage_dealt: MessageReader<DamageDealt>,
+    mut target_query: Query<(Option<&mut StatusEffects>, Option<&StatusImmunity>)>,
+    mut rng: ResMut<RunRng>,
+) {
+    for hit in damage_dealt.read() {
+        let OnHit { status, .. } = &hit.on_hit;
+        if status.is_empty() {
+            continue;
+        }
+        let Ok((effects, immunity)) = target_query.get_mut(hit.target) else {
+            continue;
+        };
+
+        let applied = status.iter().filter(|application| {
+            !immunity.is_some_and(|immunity| immunity.0.contains(&application.kind))
+                && rng.random::<f32>() < application.chance
+        });
+
+        match effects {
+            Some(mut effects) => applied.for_each(|application| effects.apply(application)),
+            None => {
+                let mut effects = StatusEffects::default();
+                applied.for_each(|application| effects.apply(application));
+                if !effects.active.is_empty() {
+                    commands.entity(hit.target).try_insert(effects);
+                }
+            }
+        }
+    }
+}
+
+/// Counts effects down and routes their damage through `DamageEvent` every `TICK_INTERVAL`.
+fn tick_statuses(
+    time: Res<Time>,
+    mut damage_events: MessageWriter<DamageEvent>,
+    mut query: Query<(Entity, &Transform, &mut StatusEffects)>,
+) {
+    for (entity, transform, mut effects) in query.iter_mut() {
+        let delta = time.delta_secs();
+        effects.tick += delta;
+        let ticking = effects.tick >= TICK_INTERVAL;
+        if ticking {
+            effects.tick -= TICK_INTERVAL;
+        }
+
+        for status in effects.active.iter_mut() {
+            if ticking && status.kind.deals_damage() {
+                damage_events.write(DamageEvent {
+                    target: entity,
+                    amount: status.magnitude * status.stacks as f32 * TICK_INTERVAL,
+                    crit: false,
+                    source: transform.translation,
+                    on_hit: OnHit::default(),
+                    status: Some(status.kind),
+                });
+            }
+            status.remaining -= delta;
+        }
+        effects.active.retain(|status| status.remaining > 0.0);
+    }
+}
+
+/// Mirrors freeze into the `Frozen` marker and tints sprites by their strongest effect.
+fn sync_status_visuals(
+    mut commands: Commands,
+    query: Query<(Entity, &StatusEffects, Has<Frozen>, Option<&Tint>), Changed<StatusEffects>>,
+) {
+    for (entity, effects, is_frozen, tint) in query.iter() {
+        let frozen = effects.has(StatusKind::Freeze);
+        if frozen && !is_frozen {
+            commands.entity(entity).try_insert(Frozen);
+        } else if !frozen && is_frozen {
+            commands.entity(entity).try_remove::<Frozen>();
+        }
+
+        match effects.tint() {
+            Some(color) if tint.is_none_or(|tint| tint.0 != color) => {
+                commands.entity(entity).try_insert(Tint(color));
+            }
+            None if tint.is_some() => {
+                commands.entity(entity).try_remove::<Tint>();
+            }
+            _ => {}
+        }
+
+        if effects.active.is_empty() {
+            commands.entity(entity).try_remove::<StatusEffects>();
+        }
+    }
+}
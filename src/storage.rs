@@ -0,0 +1,81 @@
+use std::fs;
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+/// Location of a file in the game's directory under the user's data dir.
+fn data_path(file: &str) -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("gragusi-survivors").join(file))
+}
+
+/// Location of a file in the game's directory under the user's config dir.
+fn config_path(file: &str) -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("gragusi-survivors").join(file))
+}
+
+/// Reads a RON file from the data directory. A missing file yields the default value; a
+/// corrupt one is moved aside so the next save doesn't silently overwrite it.
+pub fn load<T: DeserializeOwned + Default>(file: &str) -> T {
+    read(data_path(file))
+}
+
+pub fn save<T: Serialize>(file: &str, value: &T) {
+    write(data_path(file), file, value);
+}
+
+/// Deletes a file from the data directory, if it is there.
+pub fn remove(file: &str) {
+    let Some(path) = data_path(file) else {
+        return;
+    };
+    if let Err(err) = fs::remove_file(&path) && err.kind() != std::io::ErrorKind::NotFound {
+        warn!("Failed to remove {}: {err}", path.display());
+    }
+}
+
+/// Like [`load`], for files kept in the config directory.
+pub fn load_config<T: DeserializeOwned + Default>(file: &str) -> T {
+    read(config_path(file))
+}
+
+pub fn save_config<T: Serialize>(file: &str, value: &T) {
+    write(config_path(file), file, value);
+}
+
+fn read<T: DeserializeOwned + Default>(path: Option<PathBuf>) -> T {
+    let Some(path) = path else {
+        return T::default();
+    };
+    let Ok(contents) = fs::read_to_string(&path) else {
+        return T::default();
+    };
+
+    match ron::from_str(&contents) {
+        Ok(value) => value,
+        Err(err) => {
+            warn!("{} is corrupt ({err}), starting from defaults", path.display());
+            let _ = fs::rename(&path, path.with_extension("ron.corrupt"));
+            T::default()
+        }
+    }
+}
+
+fn write<T: Serialize>(path: Option<PathBuf>, file: &str, value: &T) {
+    let Some(path) = path else {
+        warn!("No save directory available, {file} will not be saved");
+        return;
+    };
+    let result = path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| {
+            let contents = ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())
+                .map_err(std::io::Error::other)?;
+            fs::write(&path, contents)
+        });
+    if let Err(err) = result {
+        warn!("Failed to save {}: {err}", path.display());
+    }
+}
@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+
+use bevy::asset::{LoadState, RenderAssetUsages, UntypedAssetId};
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use serde::Deserialize;
+
+use crate::GameState;
+use crate::data::RonAssetLoader;
+
+// Tileset Plugin
+pub struct TilesetPlugin;
+
+impl Plugin for TilesetPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<TileNames>()
+            .register_asset_loader(RonAssetLoader::<TileNames>::default())
+            .add_systems(Startup, load_tiles)
+            .add_systems(Update, build_tileset.run_if(in_state(GameState::Loading)));
+    }
+}
+
+/// Size of a single tile in pixels.
+pub const TILE_PIXELS: u32 = 8;
+const COLUMNS: u32 = 16;
+const ROWS: u32 = 10;
+
+/// Friendly names for tiles, loaded from `assets/data/tiles.ron`.
+#[derive(Asset, TypePath, Deserialize)]
+pub struct TileNames {
+    pub names: HashMap<String, usize>,
+}
+
+/// A tile referenced from a data file, either by atlas index or by name.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(untagged)]
+pub enum TileRef {
+    Index(usize),
+    Name(String),
+}
+
+impl From<&str> for TileRef {
+    fn from(name: &str) -> Self {
+        TileRef::Name(name.to_string())
+    }
+}
+
+/// The whole `Colored` tileset packed into one image, laid out like the original sheet so that
+/// tile `tile_NNNN.png` sits at atlas index `NNNN`. The default one has no image, which is
+/// enough for spawning sprites that are never drawn.
+#[derive(Resource, Default)]
+pub struct Tileset {
+    pub image: Handle<Image>,
+    pub layout: Handle<TextureAtlasLayout>,
+    names: HashMap<String, usize>,
+}
+
+impl Tileset {
+    /// Resolves a tile name from `tiles.ron`, or a raw `tile_NNNN` name, to its atlas index.
+    pub fn index(&self, tile: &TileRef) -> usize {
+        match tile {
+            TileRef::Index(index) => *index,
+            TileRef::Name(name) => self
+                .names
+                .get(name)
+                .copied()
+                .or_else(|| name.strip_prefix("tile_").and_then(|number| number.parse().ok()))
+                .unwrap_or_else(|| {
+                    warn!("Unknown tile name {name:?}");
+                    0
+                }),
+        }
+    }
+
+    pub fn atlas(&self, index: usize) -> TextureAtlas {
+        TextureAtlas { layout: self.layout.clone(), index }
+    }
+
+    pub fn sprite(&self, index: usize) -> Sprite {
+        Sprite::from_atlas_image(self.image.clone(), self.atlas(index))
+    }
+
+    pub fn image_node(&self, index: usize) -> ImageNode {
+        ImageNode::from_atlas_image(self.image.clone(), self.atlas(index))
+    }
+}
+
+/// Individual tile images, kept only until they have been packed into the tileset.
+#[derive(Resource)]
+struct TileImages {
+    tiles: Vec<Handle<Image>>,
+    names: Handle<TileNames>,
+}
+
+fn load_tiles(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(TileImages {
+        tiles: (0..COLUMNS * ROWS).map(|index| asset_server.load(format!("Colored/tile_{index:04}.png"))).collect(),
+        names: asset_server.load("data/tiles.ron"),
+    });
+}
+
+/// Once every tile has loaded, copies them into a single sheet and moves on to the menu.
+fn build_tileset(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    tile_images: Option<Res<TileImages>>,
+    mut images: ResMut<Assets<Image>>,
+    mut layouts: ResMut<Assets<TextureAtlasLayout>>,
+    tile_names: Res<Assets<TileNames>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Some(tile_images) = tile_images else {
+        return;
+    };
+    let settled = |id: UntypedAssetId| matches!(asset_server.get_load_state(id), Some(LoadState::Loaded | LoadState::Failed(_)));
+    if !tile_images.tiles.iter().all(|handle| settled(handle.id().untyped())) || !settled(tile_images.names.id().untyped()) {
+        return;
+    }
+
+    let mut sheet = Image::new_fill(
+        Extent3d { width: COLUMNS * TILE_PIXELS, height: ROWS * TILE_PIXELS, depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    for (index, handle) in tile_images.tiles.iter().enumerate() {
+        let Some(tile) = images.get(handle) else {
+            warn!("Missing tile image {index}");
+            continue;
+        };
+        let origin = UVec2::new(index as u32 % COLUMNS, index as u32 / COLUMNS) * TILE_PIXELS;
+        for y in 0..TILE_PIXELS.min(tile.height()) {
+            for x in 0..TILE_PIXELS.min(tile.width()) {
+                if let Ok(color) = tile.get_color_at(x, y) {
+                    let _ = sheet.set_color_at(origin.x + x, origin.y + y, color);
+                }
+            }
+        }
+    }
+
+    commands.insert_resource(Tileset {
+        image: images.add(sheet),
+        layout: layouts.add(TextureAtlasLayout::from_grid(UVec2::splat(TILE_PIXELS), COLUMNS, ROWS, None, None)),
+        names: tile_names.get(&tile_images.names).map(|names| names.names.clone()).unwrap_or_default(),
+    });
+    commands.remove_resource::<TileImages>();
+    next_state.set(GameState::Menu);
+}
@@ -0,0 +1,365 @@
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::prelude::*;
+
+use crate::input::{Action, ActionState};
+use crate::locale::Localized;
+
+// Widgets Plugin
+pub struct WidgetsPlugin;
+
+impl Plugin for WidgetsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<ButtonPressed>()
+            .add_message::<ButtonStepped>()
+            .add_systems(
+                Update,
+                (focus_buttons, press_buttons, style_buttons, scroll_to_focused, scroll_lists)
+                    .chain()
+                    .in_set(WidgetSystems),
+            );
+    }
+}
+
+pub const FONT: &str = "font/BigBlueTerm437NerdFontMono-Regular.ttf";
+
+const BUTTON_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.05);
+const FOCUSED_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.2);
+const FOCUSED_BORDER: Color = Color::srgb(1.0, 0.85, 0.2);
+const DISABLED_TEXT: Color = Color::srgb(0.4, 0.4, 0.4);
+const ROW_WIDTH: f32 = 560.0;
+const WHEEL_LINE: f32 = 24.0;
+
+/// Screens that read [`ButtonPressed`] or [`ButtonStepped`] in the same frame run after this.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WidgetSystems;
+
+/// A button that can be focused with the move actions or the mouse, and pressed with Confirm
+/// or a click. Screens tell their buttons apart with a component of their own and listen for
+/// [`ButtonPressed`].
+#[derive(Component)]
+#[require(Button)]
+pub struct FocusButton;
+
+/// The button that Confirm presses. At most one button has it, and whenever buttons are on
+/// screen one of them does.
+#[derive(Component)]
+pub struct Focused;
+
+/// A button shown greyed out that can't be focused or pressed.
+#[derive(Component)]
+pub struct Disabled;
+
+/// A button whose value changes with left and right instead of moving focus, or with the
+/// arrows on either side of it. Screens listen for [`ButtonStepped`].
+#[derive(Component)]
+pub struct Stepper;
+
+/// The text a [`row`] or [`stepper`] shows its value in, filled in by the screen.
+#[derive(Component)]
+pub struct RowValue;
+
+/// A clickable arrow inside a [`stepper`] and the step it sends.
+#[derive(Component)]
+struct StepArrow(i32);
+
+/// Text that follows the enabled state of the button it's in.
+#[derive(Component)]
+struct ButtonLabel;
+
+/// A column that scrolls with the mouse wheel and to keep the focused button in view.
+#[derive(Component)]
+pub struct ScrollList;
+
+#[derive(Message)]
+pub struct ButtonPressed(pub Entity);
+
+#[derive(Message)]
+pub struct ButtonStepped {
+    pub entity: Entity,
+    pub step: i32,
+}
+
+/// A full-screen column that centers its children, the root of every menu screen.
+pub fn screen() -> Node {
+    Node {
+        width: Val::Percent(100.0),
+        height: Val::Percent(100.0),
+        flex_direction: FlexDirection::Column,
+        align_items: AlignItems::Center,
+        justify_content: JustifyContent::Center,
+        row_gap: Val::Px(24.0),
+        ..default()
+    }
+}
+
+pub fn title(font: &Handle<Font>, text: impl Into<Localized>) -> impl Bundle {
+    (
+        text.into(),
+        TextFont {
+            font: font.clone(),
+            font_size: 40.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+    )
+}
+
+/// Small grey text for explaining the controls of a screen.
+pub fn hint(font: &Handle<Font>, text: impl Into<Localized>) -> impl Bundle {
+    (
+        text.into(),
+        TextFont {
+            font: font.clone(),
+            font_size: 16.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.7, 0.7, 0.7)),
+        TextLayout::new_with_justify(Justify::Center),
+    )
+}
+
+/// A column of buttons taller than the screen, `height` being how much of it to show.
+pub fn list(height: Val) -> impl Bundle {
+    (
+        ScrollList,
+        Node {
+            height,
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            row_gap: Val::Px(4.0),
+            padding: UiRect::horizontal(Val::Px(4.0)),
+            overflow: Overflow::scroll_y(),
+            ..default()
+        },
+        ScrollPosition::default(),
+    )
+}
+
+pub fn button(font: &Handle<Font>, label: impl Into<Localized>) -> impl Bundle {
+    (
+        FocusButton,
+        Node {
+            width: Val::Px(320.0),
+            padding: UiRect::all(Val::Px(8.0)),
+            border: UiRect::all(Val::Px(2.0)),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        BackgroundColor(BUTTON_COLOR),
+        BorderColor::all(Color::NONE),
+        children![label_text(font, label, 24.0)],
+    )
+}
+
+/// A wide button with its label on the left and a [`RowValue`] on the right.
+pub fn row(font: &Handle<Font>, label: impl Into<Localized>) -> impl Bundle {
+    (
+        FocusButton,
+        row_node(),
+        BackgroundColor(BUTTON_COLOR),
+        BorderColor::all(Color::NONE),
+        children![(label_text(font, label, 18.0), Node { flex_grow: 1.0, ..default() }), value_text(font)],
+    )
+}
+
+/// A [`row`] with arrows around its value, see [`Stepper`].
+pub fn stepper(font: &Handle<Font>, label: impl Into<Localized>) -> impl Bundle {
+    (
+        FocusButton,
+        Stepper,
+        row_node(),
+        BackgroundColor(BUTTON_COLOR),
+        BorderColor::all(Color::NONE),
+        children![
+            (label_text(font, label, 18.0), Node { flex_grow: 1.0, ..default() }),
+            step_arrow(font, -1),
+            value_text(font),
+            step_arrow(font, 1),
+        ],
+    )
+}
+
+fn row_node() -> Node {
+    Node {
+        width: Val::Px(ROW_WIDTH),
+        padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+        border: UiRect::all(Val::Px(2.0)),
+        align_items: AlignItems::Center,
+        column_gap: Val::Px(8.0),
+        flex_shrink: 0.0,
+        ..default()
+    }
+}
+
+fn label_text(font: &Handle<Font>, label: impl Into<Localized>, font_size: f32) -> impl Bundle {
+    (
+        ButtonLabel,
+        label.into(),
+        TextFont {
+            font: font.clone(),
+            font_size,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+    )
+}
+
+fn value_text(font: &Handle<Font>) -> impl Bundle {
+    (
+        ButtonLabel,
+        RowValue,
+        Text::default(),
+        TextFont {
+            font: font.clone(),
+            font_size: 18.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        TextLayout::new_with_justify(Justify::Center),
+        Node {
+            min_width: Val::Px(180.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+    )
+}
+
+fn step_arrow(font: &Handle<Font>, step: i32) -> impl Bundle {
+    (
+        Button,
+        StepArrow(step),
+        ButtonLabel,
+        Text::new(if step < 0 { "<" } else { ">" }),
+        TextFont {
+            font: font.clone(),
+            font_size: 18.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+    )
+}
+
+/// Moves focus with the move actions, in reading order, and to whatever the mouse hovers.
+fn focus_buttons(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    button_query: Query<(Entity, &UiGlobalTransform, &ComputedNode, Ref<Interaction>, Has<Focused>), (With<FocusButton>, Without<Disabled>)>,
+    focused_query: Query<(Entity, Has<Stepper>), With<Focused>>,
+) {
+    let mut buttons: Vec<_> = button_query.iter().collect();
+    // Reading order is only known once the buttons have been laid out
+    if buttons.is_empty() || buttons.iter().any(|(_, _, node, ..)| node.size() == Vec2::ZERO) {
+        return;
+    }
+    buttons.sort_by(|(_, a, ..), (_, b, ..)| a.translation.y.total_cmp(&b.translation.y).then(a.translation.x.total_cmp(&b.translation.x)));
+
+    let current = buttons.iter().position(|(.., focused)| *focused);
+    let count = buttons.len();
+    let step = |action| actions.just_pressed(action) as usize;
+    // Left and right belong to a focused stepper
+    let sideways = !focused_query.iter().any(|(_, stepper)| stepper) as usize;
+    let forward = step(Action::MoveDown) + step(Action::MoveRight) * sideways;
+    let back = step(Action::MoveUp) + step(Action::MoveLeft) * sideways;
+    let hovered = buttons.iter().position(|(_, _, _, interaction, _)| interaction.is_changed() && **interaction == Interaction::Hovered);
+
+    let target = match (hovered, current) {
+        (Some(hovered), _) => hovered,
+        (None, Some(current)) => (current + count * 2 + forward - back) % count,
+        (None, None) => 0,
+    };
+    if current != Some(target) {
+        for (entity, _) in focused_query.iter() {
+            commands.entity(entity).remove::<Focused>();
+        }
+        commands.entity(buttons[target].0).insert(Focused);
+    }
+}
+
+fn press_buttons(
+    actions: Res<ActionState>,
+    button_query: Query<(Entity, &Interaction), (With<FocusButton>, Without<Disabled>, Changed<Interaction>)>,
+    arrow_query: Query<(&StepArrow, &ChildOf, &Interaction), Changed<Interaction>>,
+    stepper_query: Query<(), (With<Stepper>, Without<Disabled>)>,
+    focused_query: Query<(Entity, Has<Stepper>), (With<Focused>, Without<Disabled>)>,
+    mut pressed: MessageWriter<ButtonPressed>,
+    mut stepped: MessageWriter<ButtonStepped>,
+) {
+    for (entity, interaction) in button_query.iter() {
+        if *interaction == Interaction::Pressed {
+            pressed.write(ButtonPressed(entity));
+        }
+    }
+    for (StepArrow(step), child_of, interaction) in arrow_query.iter() {
+        if *interaction == Interaction::Pressed && stepper_query.contains(child_of.parent()) {
+            stepped.write(ButtonStepped { entity: child_of.parent(), step: *step });
+        }
+    }
+    let Ok((entity, stepper)) = focused_query.single() else {
+        return;
+    };
+    if actions.just_pressed(Action::Confirm) {
+        pressed.write(ButtonPressed(entity));
+    }
+    if stepper {
+        let step = actions.just_pressed(Action::MoveRight) as i32 - actions.just_pressed(Action::MoveLeft) as i32;
+        if step != 0 {
+            stepped.write(ButtonStepped { entity, step });
+        }
+    }
+}
+
+fn style_buttons(
+    mut button_query: Query<(&mut BackgroundColor, &mut BorderColor, &Children, Has<Focused>, Has<Disabled>), With<FocusButton>>,
+    mut text_query: Query<&mut TextColor, With<ButtonLabel>>,
+) {
+    for (mut background, mut border, children, focused, disabled) in button_query.iter_mut() {
+        background.set_if_neq(BackgroundColor(if focused { FOCUSED_COLOR } else { BUTTON_COLOR }));
+        border.set_if_neq(BorderColor::all(if focused { FOCUSED_BORDER } else { Color::NONE }));
+        for child in children.iter() {
+            if let Ok(mut color) = text_query.get_mut(child) {
+                color.set_if_neq(TextColor(if disabled { DISABLED_TEXT } else { Color::WHITE }));
+            }
+        }
+    }
+}
+
+/// Scrolls the list a newly focused button is in until the button is in view.
+fn scroll_to_focused(
+    focused_query: Query<(Entity, &UiGlobalTransform, &ComputedNode), Added<Focused>>,
+    parent_query: Query<&ChildOf>,
+    mut list_query: Query<(&UiGlobalTransform, &ComputedNode, &mut ScrollPosition), With<ScrollList>>,
+) {
+    for (entity, transform, node) in focused_query.iter() {
+        let Some(list) = parent_query.iter_ancestors(entity).find(|ancestor| list_query.contains(*ancestor)) else {
+            continue;
+        };
+        let Ok((list_transform, list_node, mut scroll)) = list_query.get_mut(list) else {
+            continue;
+        };
+        // Layout is in physical pixels, scroll positions in logical ones
+        let top = transform.translation.y - node.size().y / 2.0;
+        let bottom = transform.translation.y + node.size().y / 2.0;
+        let list_top = list_transform.translation.y - list_node.size().y / 2.0;
+        let list_bottom = list_transform.translation.y + list_node.size().y / 2.0;
+        let offset = (top - list_top).min(0.0) + (bottom - list_bottom).max(0.0);
+        if offset != 0.0 {
+            scroll.y += offset * list_node.inverse_scale_factor();
+        }
+    }
+}
+
+fn scroll_lists(mut wheel: MessageReader<MouseWheel>, mut list_query: Query<(&ComputedNode, &mut ScrollPosition), With<ScrollList>>) {
+    let scrolled: f32 = wheel
+        .read()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y * WHEEL_LINE,
+            MouseScrollUnit::Pixel => event.y,
+        })
+        .sum();
+    if scrolled != 0.0 {
+        for (node, mut scroll) in list_query.iter_mut() {
+            let end = (node.content_size().y - node.size().y).max(0.0) * node.inverse_scale_factor();
+            scroll.y = (scroll.y - scrolled).clamp(0.0, end);
+        }
+    }
+}
@@ -0,0 +1,421 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::tileset::Tileset;
+use crate::{Bullet, Enemy, GameState, Player, RunStats};
+
+// World Plugin
+pub struct WorldPlugin;
+
+impl Plugin for WorldPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ChunkManager>()
+            .init_resource::<WalkabilityGrid>()
+            .add_systems(Update, stream_chunks.run_if(in_state(GameState::Playing)))
+            .add_systems(
+                PostUpdate,
+                (resolve_obstacle_collisions, block_bullets, scroll_parallax)
+                    .before(TransformSystems::Propagate)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(OnEnter(GameState::Menu), cleanup_world);
+    }
+}
+
+/// Size of one tile in world units: 8 px tiles drawn at 4x.
+pub const TILE_SIZE: f32 = 32.0;
+/// Width and height of a chunk in tiles.
+pub const CHUNK_SIZE: i32 = 16;
+/// Chunks within this many chunks of the player are kept loaded.
+const LOAD_RADIUS: i32 = 2;
+/// Distance from the player in every direction that is always loaded, so always has its
+/// obstacles.
+pub const LOADED_RANGE: f32 = (LOAD_RADIUS * CHUNK_SIZE) as f32 * TILE_SIZE;
+/// Tiles around the spawn point that are always left clear.
+const SPAWN_CLEARING: i32 = 3;
+/// Collision radius of the player and enemies against obstacles.
+const BODY_RADIUS: f32 = 12.0;
+/// How far outside the visible area enemies are spawned.
+const SPAWN_MARGIN: f32 = 64.0;
+/// How fast the far background moves compared to the camera; lower looks further away.
+const PARALLAX_FACTOR: f32 = 0.5;
+/// Copies of the background pattern on each side of the camera, enough to fill the view at
+/// the widest zoom.
+const PARALLAX_REPEATS: i32 = 2;
+
+// Tile names from `assets/data/tiles.ron`
+const GROUND_TILES: [&str; 2] = ["pebbles", "sprouts"];
+const TREE_TILES: [&str; 3] = ["tree", "bush", "pine"];
+const ROCK_TILES: [&str; 3] = ["mushroom", "boulder", "stump"];
+const WALL_TILE: &str = "wall";
+
+/// The tile a world position falls in. Tile `(0, 0)` is centered on the origin.
+pub fn tile_at(position: Vec2) -> IVec2 {
+    (position / TILE_SIZE).round().as_ivec2()
+}
+
+pub fn tile_center(tile: IVec2) -> Vec2 {
+    tile.as_vec2() * TILE_SIZE
+}
+
+pub fn chunk_of(tile: IVec2) -> IVec2 {
+    tile.div_euclid(IVec2::splat(CHUNK_SIZE))
+}
+
+struct LoadedChunk {
+    entity: Entity,
+    solid: Vec<IVec2>,
+}
+
+/// Tracks which chunks are loaded. Chunks are generated from the run seed alone, so one that is
+/// unloaded and later streamed back in comes back exactly the same.
+#[derive(Resource, Default)]
+pub struct ChunkManager {
+    chunks: HashMap<IVec2, LoadedChunk>,
+    center: Option<IVec2>,
+}
+
+/// Which tiles can be walked on, covering every chunk within `LOAD_RADIUS` of the player's chunk.
+/// Tiles outside the grid aren't loaded and count as neither walkable nor solid.
+#[derive(Resource, Default)]
+pub struct WalkabilityGrid {
+    origin: IVec2,
+    size: IVec2,
+    walkable: Vec<bool>,
+}
+
+impl WalkabilityGrid {
+    /// First tile covered by the grid, in its bottom-left corner.
+    pub fn origin(&self) -> IVec2 {
+        self.origin
+    }
+
+    /// Width and height of the grid in tiles.
+    pub fn size(&self) -> IVec2 {
+        self.size
+    }
+
+    /// Position of a tile in `walkable`, if the grid covers it.
+    pub fn index(&self, tile: IVec2) -> Option<usize> {
+        let local = tile - self.origin;
+        (local.cmpge(IVec2::ZERO).all() && local.cmplt(self.size).all()).then(|| (local.y * self.size.x + local.x) as usize)
+    }
+
+    pub fn is_walkable(&self, tile: IVec2) -> bool {
+        self.index(tile).is_some_and(|index| self.walkable[index])
+    }
+
+    pub fn is_solid(&self, tile: IVec2) -> bool {
+        self.index(tile).is_some_and(|index| !self.walkable[index])
+    }
+}
+
+#[derive(Component)]
+pub struct Chunk;
+
+/// Faint specks behind the terrain that scroll slower than the camera. The same chunk-sized
+/// pattern repeats across the layer, which jumps back by one pattern whenever it falls a whole
+/// one behind.
+#[derive(Component)]
+struct ParallaxLayer;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Terrain {
+    Empty,
+    Ground(&'static str),
+    Tree(&'static str),
+    Rock(&'static str),
+    Wall,
+}
+
+impl Terrain {
+    fn is_solid(self) -> bool {
+        matches!(self, Terrain::Tree(_) | Terrain::Rock(_) | Terrain::Wall)
+    }
+}
+
+/// Deterministic pseudo-random value in [0, 1) for a tile, so a chunk looks the same every time
+/// it is generated during a run.
+fn noise(seed: u64, tile: IVec2, salt: u64) -> f32 {
+    // SplitMix64 over the seed, coordinates and salt
+    let mut z = seed
+        ^ (tile.x as i64 as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        ^ (tile.y as i64 as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F)
+        ^ salt.wrapping_mul(0x1656_67B1_9E37_79F9);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    (z >> 40) as f32 / (1u64 << 24) as f32
+}
+
+fn pick(tiles: &[&'static str], roll: f32) -> &'static str {
+    tiles[((roll * tiles.len() as f32) as usize).min(tiles.len() - 1)]
+}
+
+/// Lays out every tile of a chunk: scattered ground details, trees and rocks, and now and then
+/// a straight stretch of ruined wall.
+fn generate_chunk(seed: u64, chunk: IVec2) -> Vec<(IVec2, Terrain)> {
+    let origin = chunk * CHUNK_SIZE;
+    let mut tiles = HashMap::new();
+
+    for y in 0..CHUNK_SIZE {
+        for x in 0..CHUNK_SIZE {
+            let tile = origin + IVec2::new(x, y);
+            let roll = noise(seed, tile, 0);
+            let variant = noise(seed, tile, 1);
+            let terrain = if roll < 0.025 {
+                Terrain::Tree(pick(&TREE_TILES, variant))
+            } else if roll < 0.04 {
+                Terrain::Rock(pick(&ROCK_TILES, variant))
+            } else if roll < 0.14 {
+                Terrain::Ground(pick(&GROUND_TILES, variant))
+            } else {
+                Terrain::Empty
+            };
+            tiles.insert(tile, terrain);
+        }
+    }
+
+    if noise(seed, chunk, 2) < 0.35 {
+        let length = 3 + (noise(seed, chunk, 3) * 5.0) as i32;
+        let horizontal = noise(seed, chunk, 4) < 0.5;
+        let start = origin
+            + IVec2::new(
+                (noise(seed, chunk, 5) * (CHUNK_SIZE - length) as f32) as i32,
+                (noise(seed, chunk, 6) * (CHUNK_SIZE - length) as f32) as i32,
+            );
+        for i in 0..length {
+            let offset = if horizontal { IVec2::new(i, 0) } else { IVec2::new(0, i) };
+            tiles.insert(start + offset, Terrain::Wall);
+        }
+    }
+
+    tiles
+        .into_iter()
+        .filter(|(tile, terrain)| {
+            *terrain != Terrain::Empty && !(terrain.is_solid() && tile.abs().max_element() <= SPAWN_CLEARING)
+        })
+        .collect()
+}
+
+/// Generates chunks as the player approaches them, drops the ones left behind and rebuilds the
+/// walkability grid whenever the player crosses into another chunk.
+fn stream_chunks(
+    mut commands: Commands,
+    tileset: Res<Tileset>,
+    mut manager: ResMut<ChunkManager>,
+    mut grid: ResMut<WalkabilityGrid>,
+    stats: Res<RunStats>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    let Ok(player_transform) = player_query.single() else {
+        return;
+    };
+    let center = chunk_of(tile_at(player_transform.translation.truncate()));
+    if manager.center == Some(center) {
+        return;
+    }
+    manager.center = Some(center);
+
+    // Unload chunks that are out of range, leaving a one chunk margin so walking back and forth
+    // over a border doesn't regenerate the same chunks
+    manager.chunks.retain(|chunk, loaded| {
+        let keep = (*chunk - center).abs().max_element() <= LOAD_RADIUS + 1;
+        if !keep {
+            commands.entity(loaded.entity).despawn();
+        }
+        keep
+    });
+
+    for y in -LOAD_RADIUS..=LOAD_RADIUS {
+        for x in -LOAD_RADIUS..=LOAD_RADIUS {
+            let chunk = center + IVec2::new(x, y);
+            if manager.chunks.contains_key(&chunk) {
+                continue;
+            }
+
+            let tiles = generate_chunk(stats.seed, chunk);
+            let entity = commands
+                .spawn((Chunk, Transform::default(), Visibility::default()))
+                .with_children(|parent| {
+                    for (tile, terrain) in &tiles {
+                        let (name, z) = match *terrain {
+                            Terrain::Ground(name) => (name, -20.0),
+                            Terrain::Tree(name) | Terrain::Rock(name) => (name, -10.0),
+                            Terrain::Wall => (WALL_TILE, -10.0),
+                            Terrain::Empty => continue,
+                        };
+                        let position = tile_center(*tile);
+                        parent.spawn((
+                            tileset.sprite(tileset.index(&name.into())),
+                            Transform::from_xyz(position.x, position.y, z).with_scale(Vec3::splat(4.0)),
+                        ));
+                    }
+                })
+                .id();
+
+            let solid = tiles.iter().filter(|(_, terrain)| terrain.is_solid()).map(|(tile, _)| *tile).collect();
+            manager.chunks.insert(chunk, LoadedChunk { entity, solid });
+        }
+    }
+
+    let origin = (center - LOAD_RADIUS) * CHUNK_SIZE;
+    let size = IVec2::splat((2 * LOAD_RADIUS + 1) * CHUNK_SIZE);
+    *grid = WalkabilityGrid { origin, size, walkable: vec![true; (size.x * size.y) as usize] };
+    for loaded in manager.chunks.values() {
+        for tile in &loaded.solid {
+            if let Some(index) = grid.index(*tile) {
+                grid.walkable[index] = false;
+            }
+        }
+    }
+}
+
+/// A random walkable point just outside the visible area, or `None` if every try was blocked.
+pub fn offscreen_spawn_point(grid: &WalkabilityGrid, view: Rect, rng: &mut impl Rng) -> Option<Vec2> {
+    let area = view.inflate(SPAWN_MARGIN);
+    let perimeter = 2.0 * (area.width() + area.height());
+
+    for _ in 0..10 {
+        // Walk a random distance around the edge of the area
+        let (width, height) = (area.width(), area.height());
+        let distance = rng.random_range(0.0..perimeter);
+        let point = if distance < width {
+            Vec2::new(area.min.x + distance, area.min.y)
+        } else if distance < width + height {
+            Vec2::new(area.max.x, area.min.y + distance - width)
+        } else if distance < 2.0 * width + height {
+            Vec2::new(area.max.x - (distance - width - height), area.max.y)
+        } else {
+            Vec2::new(area.min.x, area.max.y - (distance - 2.0 * width - height))
+        };
+
+        if grid.is_walkable(tile_at(point)) {
+            return Some(point);
+        }
+    }
+    None
+}
+
+/// The part of the world a camera currently shows.
+pub fn visible_area(camera: &Camera, transform: &GlobalTransform) -> Option<Rect> {
+    let viewport = camera.logical_viewport_rect()?;
+    let a = camera.viewport_to_world_2d(transform, viewport.min).ok()?;
+    let b = camera.viewport_to_world_2d(transform, viewport.max).ok()?;
+    Some(Rect::from_corners(a, b))
+}
+
+/// Pushes the player and enemies out of any solid tile they moved into.
+fn resolve_obstacle_collisions(
+    grid: Res<WalkabilityGrid>,
+    mut body_query: Query<&mut Transform, Or<(With<Player>, With<Enemy>)>>,
+) {
+    let half = TILE_SIZE / 2.0;
+
+    for mut transform in body_query.iter_mut() {
+        let mut position = transform.translation.truncate();
+        let min = tile_at(position - BODY_RADIUS);
+        let max = tile_at(position + BODY_RADIUS);
+
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                let tile = IVec2::new(x, y);
+                if !grid.is_solid(tile) {
+                    continue;
+                }
+
+                let center = tile_center(tile);
+                let closest = position.clamp(center - half, center + half);
+                let offset = position - closest;
+                let distance = offset.length();
+
+                if distance > 0.0 {
+                    if distance < BODY_RADIUS {
+                        position += offset / distance * (BODY_RADIUS - distance);
+                    }
+                } else {
+                    // Center is inside the tile, push out along the shallowest axis
+                    let delta = position - center;
+                    if delta.x.abs() > delta.y.abs() {
+                        position.x = center.x + (half + BODY_RADIUS) * delta.x.signum();
+                    } else {
+                        position.y = center.y + (half + BODY_RADIUS) * delta.y.signum();
+                    }
+                }
+            }
+        }
+
+        if position != transform.translation.truncate() {
+            transform.translation.x = position.x;
+            transform.translation.y = position.y;
+        }
+    }
+}
+
+fn block_bullets(
+    mut commands: Commands,
+    grid: Res<WalkabilityGrid>,
+    bullet_query: Query<(Entity, &Transform), With<Bullet>>,
+) {
+    for (entity, transform) in bullet_query.iter() {
+        if grid.is_solid(tile_at(transform.translation.truncate())) {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// Spawns the background layer on the first frame of a run and keeps it trailing the camera.
+fn scroll_parallax(
+    mut commands: Commands,
+    tileset: Res<Tileset>,
+    stats: Res<RunStats>,
+    camera_query: Query<&Transform, (With<Camera2d>, Without<ParallaxLayer>)>,
+    mut layer_query: Query<&mut Transform, With<ParallaxLayer>>,
+) {
+    let Ok(camera_transform) = camera_query.single() else {
+        return;
+    };
+    let period = CHUNK_SIZE as f32 * TILE_SIZE;
+    let camera = camera_transform.translation.truncate();
+    let position = camera - (camera * (1.0 - PARALLAX_FACTOR)).rem_euclid(Vec2::splat(period));
+
+    let Ok(mut transform) = layer_query.single_mut() else {
+        commands.spawn((ParallaxLayer, Transform::from_xyz(position.x, position.y, -30.0), Visibility::default())).with_children(|layer| {
+            for y in 0..CHUNK_SIZE {
+                for x in 0..CHUNK_SIZE {
+                    let tile = IVec2::new(x, y);
+                    if noise(stats.seed, tile, 7) >= 0.04 {
+                        continue;
+                    }
+                    let mut sprite = tileset.sprite(tileset.index(&pick(&GROUND_TILES, noise(stats.seed, tile, 8)).into()));
+                    sprite.color = Color::srgba(1.0, 1.0, 1.0, 0.2);
+                    for repeat_y in -PARALLAX_REPEATS..=PARALLAX_REPEATS {
+                        for repeat_x in -PARALLAX_REPEATS..=PARALLAX_REPEATS {
+                            let offset = IVec2::new(repeat_x, repeat_y).as_vec2() * period + tile.as_vec2() * TILE_SIZE;
+                            // Drawn smaller than the terrain, as if further away
+                            layer.spawn((sprite.clone(), Transform::from_xyz(offset.x, offset.y, 0.0).with_scale(Vec3::splat(2.0))));
+                        }
+                    }
+                }
+            }
+        });
+        return;
+    };
+    transform.translation.x = position.x;
+    transform.translation.y = position.y;
+}
+
+fn cleanup_world(
+    mut commands: Commands,
+    mut manager: ResMut<ChunkManager>,
+    mut grid: ResMut<WalkabilityGrid>,
+    chunk_query: Query<Entity, Or<(With<Chunk>, With<ParallaxLayer>)>>,
+) {
+    for entity in chunk_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    *manager = ChunkManager::default();
+    *grid = WalkabilityGrid::default();
+}